@@ -5,6 +5,8 @@
 //!
 //! ## Platform-specific
 //!
+//! - **Linux:** The `CLIPBOARD` selection is used by default. The `PRIMARY` selection can be
+//!   accessed with `ClipboardExtUnix::with_kind`.
 //! - **Android / iOS:** Unsupported
 //!
 //! ```rust,ignore
@@ -13,12 +15,29 @@
 //! let content = cliboard.read_text();
 //! ```
 //!
+//! Multiple formats can be written in a single clipboard transaction, for example plain text
+//! alongside an application specific format:
+//!
+//! ```rust,ignore
+//! let mut cliboard = Clipboard::new();
+//! cliboard.put_formats(&[
+//!   ClipboardFormat::from("plain text fallback"),
+//!   ClipboardFormat::new("application/x-my-app", my_app_bytes),
+//! ]);
+//! let content = cliboard.read_format("application/x-my-app");
+//! ```
+//!
+
+use std::path::{Path, PathBuf};
 
-use crate::platform_impl::Clipboard as ClipboardPlatform;
+use crate::{
+  icon::{BadIcon, RgbaIcon},
+  platform_impl::Clipboard as ClipboardPlatform,
+};
 
 #[derive(Debug, Clone, Default)]
 /// Object that allows you to access the `Clipboard` instance.
-pub struct Clipboard(ClipboardPlatform);
+pub struct Clipboard(pub(crate) ClipboardPlatform);
 
 impl Clipboard {
   /// Creates a new `Clipboard` instance.
@@ -43,28 +62,228 @@ impl Clipboard {
   ///
   /// ## Platform-specific
   ///
+  /// - **Linux:** This spins a nested GTK main loop until the content is received and must be
+  ///   called from the event loop thread. Prefer [`Clipboard::request_text`].
   /// - **Android / iOS:** Unsupported
   pub fn read_text(&self) -> Option<String> {
     self.0.read_text()
   }
+
+  /// Requests the content in the clipboard as plain text without blocking.
+  ///
+  /// `callback` is called with the content once it has been received. This can be called from
+  /// any thread; to handle the content in the event loop, send it with an
+  /// [`EventLoopProxy`](crate::event_loop::EventLoopProxy) from `callback`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The request is dispatched to the GTK main context and `callback` is called on
+  ///   the event loop thread.
+  /// - **Windows / macOS:** The clipboard is read right away and `callback` is called on the
+  ///   calling thread.
+  /// - **Android / iOS:** Unsupported, `callback` is called with `None`.
+  pub fn request_text<F: FnOnce(Option<String>) + Send + 'static>(&self, callback: F) {
+    self.0.request_text(callback)
+  }
+
+  /// Writes all the given formats into the clipboard in a single transaction,
+  /// replacing its previous content.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** [`ClipboardFormat::TEXT`] is offered under all the usual text targets
+  ///   (`UTF8_STRING`, `TEXT`, `STRING`, `text/plain`, ...).
+  /// - **Windows:** [`ClipboardFormat::TEXT`] is converted to `CF_UNICODETEXT`, with the invalid
+  ///   UTF-8 sequences replaced by `U+FFFD`.
+  /// - **Android / iOS:** Unsupported
+  pub fn put_formats(&mut self, formats: &[ClipboardFormat]) {
+    self.0.put_formats(formats);
+  }
+
+  /// The content in the clipboard for the given format as raw bytes.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / iOS:** Unsupported
+  pub fn read_format(&self, format: &str) -> Option<Vec<u8>> {
+    self.0.read_format(format)
+  }
+
+  /// Requests the content in the clipboard for the given format as raw bytes without blocking.
+  ///
+  /// See [`Clipboard::request_text`] for how and where `callback` is called.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / iOS:** Unsupported, `callback` is called with `None`.
+  pub fn request_format<F: FnOnce(Option<Vec<u8>>) + Send + 'static>(
+    &self,
+    format: &str,
+    callback: F,
+  ) {
+    self.0.request_format(format, callback)
+  }
+
+  /// Hands the current clipboard content over to the clipboard manager, so that it stays
+  /// available after the application exits.
+  ///
+  /// Call it after writing to the clipboard, e.g. when handling [`Event::LoopDestroyed`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Stores the content with `gtk_clipboard_store`, which blocks until the clipboard
+  ///   manager copied it or a timeout elapses. Does nothing if no clipboard manager is running.
+  /// - **Windows / macOS / Android / iOS:** Unsupported, the content is already kept by the
+  ///   system.
+  ///
+  /// [`Event::LoopDestroyed`]: crate::event::Event::LoopDestroyed
+  pub fn store(&mut self) {
+    self.0.store();
+  }
+
+  /// The identifiers of the formats the clipboard content is currently offered in.
+  ///
+  /// The identifiers can be passed to [`Clipboard::read_format`] and
+  /// [`Clipboard::request_format`] as is.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Returns the `TARGETS` of the selection, including the meta targets such as
+  ///   `TIMESTAMP`. Like [`Clipboard::read_text`], it blocks until the owner answers.
+  /// - **Windows:** The predefined formats are named after their constant, e.g. `CF_UNICODETEXT`.
+  /// - **macOS:** Returns the pasteboard types, which are UTIs such as `public.utf8-plain-text`.
+  /// - **Android / iOS:** Unsupported
+  pub fn available_formats(&self) -> Vec<String> {
+    self.0.available_formats()
+  }
+
+  /// Writes HTML into the clipboard, with `alt_text` as the plain text fallback for the
+  /// applications that don't support rich text.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The HTML is offered as `text/html`.
+  /// - **Windows:** The HTML is offered as `HTML Format`.
+  /// - **macOS:** The HTML is written as `public.html`.
+  /// - **Android / iOS:** Unsupported
+  pub fn write_html(&mut self, html: impl AsRef<str>, alt_text: impl AsRef<str>) {
+    self.0.write_html(html.as_ref(), alt_text.as_ref());
+  }
+
+  /// The content in the clipboard as HTML.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only the copied fragment is returned, without the surrounding context.
+  /// - **Android / iOS:** Unsupported
+  pub fn read_html(&self) -> Option<String> {
+    self.0.read_html()
+  }
+
+  /// Writes an image into the clipboard from 32bpp RGBA data.
+  ///
+  /// The length of `rgba` must be divisible by 4, and `width * height` must equal
+  /// `rgba.len() / 4`. Otherwise, this will return a `BadIcon` error, exactly like
+  /// [`Icon::from_rgba`](crate::window::Icon::from_rgba). The image can't be empty either,
+  /// a width or height of 0 returns [`BadIcon::ZeroDimension`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** The image is written as `public.png`.
+  /// - **Android / iOS:** Unsupported
+  pub fn write_image(&mut self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon> {
+    let image = RgbaIcon::from_non_empty_rgba(rgba, width, height)?;
+    self.0.write_image(&ClipboardImage {
+      rgba: image.rgba,
+      width: image.width,
+      height: image.height,
+    });
+    Ok(())
+  }
+
+  /// The image in the clipboard as 32bpp RGBA data.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Only `public.png` images are read.
+  /// - **Android / iOS:** Unsupported
+  pub fn read_image(&self) -> Option<ClipboardImage> {
+    self.0.read_image()
+  }
+
+  /// Writes a list of files into the clipboard, to be copied or moved by file managers on paste.
+  ///
+  /// The paths must be absolute.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The files are offered as `x-special/gnome-copied-files` and `text/uri-list`,
+  ///   together with `application/x-kde-cutselection` and the plain text paths.
+  /// - **Windows:** The files are offered as `CF_HDROP` with a `Preferred DropEffect`.
+  /// - **macOS:** The files are written as file URLs. [`FileOperation::Cut`] is unsupported.
+  /// - **Android / iOS:** Unsupported
+  pub fn write_files<P: AsRef<Path>>(&mut self, paths: &[P], operation: FileOperation) {
+    let paths: Vec<PathBuf> = paths.iter().map(|p| p.as_ref().to_path_buf()).collect();
+    self.0.write_files(&paths, operation);
+  }
+
+  /// The list of files in the clipboard, together with the operation requested on paste.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** The operation is always [`FileOperation::Copy`].
+  /// - **Android / iOS:** Unsupported
+  pub fn read_files(&self) -> Option<ClipboardFiles> {
+    self.0.read_files()
+  }
+}
+
+/// An image read from the clipboard as 32bpp RGBA data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardImage {
+  /// The pixels, row by row from the top-left corner, 4 bytes per pixel.
+  pub rgba: Vec<u8>,
+  pub width: u32,
+  pub height: u32,
+}
+
+/// A list of files read from the clipboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardFiles {
+  pub paths: Vec<PathBuf>,
+  pub operation: FileOperation,
+}
+
+/// Describes what should happen to the files in the clipboard when they are pasted.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileOperation {
+  /// The files are copied and the originals are kept.
+  Copy,
+  /// The files are moved.
+  Cut,
 }
 
 /// Identifier of a clipboard format.
-pub(crate) type FormatId = &'static str;
+///
+/// This is a MIME type on Linux, a Uniform Type Identifier on macOS and the name of a
+/// registered clipboard format on Windows.
+pub type FormatId = &'static str;
 
-/// Object that allows you to access the `ClipboardFormat`.
+/// Data to be put on the clipboard, together with the identifier of its format.
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
-pub(crate) struct ClipboardFormat {
-  pub(crate) identifier: FormatId,
-  pub(crate) data: Vec<u8>,
+pub struct ClipboardFormat {
+  pub identifier: FormatId,
+  pub data: Vec<u8>,
 }
 
 // todo add more formats
 impl ClipboardFormat {
   #[cfg(any(target_os = "macos", target_os = "ios"))]
+  /// UTF-8 encoded plain text.
   pub const TEXT: &'static str = "public.utf8-plain-text";
   #[cfg(any(target_os = "windows", target_os = "android"))]
+  /// UTF-8 encoded plain text.
   pub const TEXT: &'static str = "text/plain";
   #[cfg(any(
     target_os = "linux",
@@ -73,10 +292,12 @@ impl ClipboardFormat {
     target_os = "netbsd",
     target_os = "openbsd"
   ))]
+  /// UTF-8 encoded plain text.
   pub const TEXT: &'static str = "UTF8_STRING";
 }
 
 impl ClipboardFormat {
+  /// Creates a new `ClipboardFormat` from its identifier and raw data.
   pub fn new(identifier: FormatId, data: impl Into<Vec<u8>>) -> Self {
     let data = data.into();
     ClipboardFormat { identifier, data }
@@ -95,3 +316,19 @@ impl From<&str> for ClipboardFormat {
     src.to_string().into()
   }
 }
+
+#[test]
+fn write_empty_image() {
+  let mut clipboard = Clipboard::new();
+  assert!(matches!(
+    clipboard.write_image(vec![], 0, 0),
+    Err(BadIcon::ZeroDimension {
+      width: 0,
+      height: 0
+    })
+  ));
+  assert!(matches!(
+    clipboard.write_image(vec![], 4, 0),
+    Err(BadIcon::ZeroDimension { .. })
+  ));
+}
@@ -45,7 +45,7 @@ use crate::{
   keyboard::{self, ModifiersState},
   menu::{MenuId, MenuType},
   platform_impl,
-  window::{Theme, WindowId},
+  window::{DropAction, Theme, WindowId},
 };
 
 /// Describes a generic event.
@@ -107,6 +107,16 @@ pub enum Event<'a, T: 'static> {
   /// - **iOS / Android:** Unsupported.
   GlobalShortcutEvent(AcceleratorId),
 
+  /// Emitted when the content of the system clipboard has changed, either by this application
+  /// or by another one.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Emitted when the owner of the `CLIPBOARD` selection changes. Changes to the
+  ///   `PRIMARY` selection are not reported.
+  /// - **macOS / iOS / Android:** Unsupported.
+  ClipboardChanged,
+
   /// Emitted when the application has been suspended.
   Suspended,
 
@@ -203,6 +213,7 @@ impl<T: Clone> Clone for Event<'static, T> {
         position: *position,
       },
       GlobalShortcutEvent(accelerator_id) => GlobalShortcutEvent(*accelerator_id),
+      ClipboardChanged => ClipboardChanged,
     }
   }
 }
@@ -240,6 +251,7 @@ impl<'a, T> Event<'a, T> {
         position,
       }),
       GlobalShortcutEvent(accelerator_id) => Ok(GlobalShortcutEvent(accelerator_id)),
+      ClipboardChanged => Ok(ClipboardChanged),
     }
   }
 
@@ -279,6 +291,7 @@ impl<'a, T> Event<'a, T> {
         position,
       }),
       GlobalShortcutEvent(accelerator_id) => Some(GlobalShortcutEvent(accelerator_id)),
+      ClipboardChanged => Some(ClipboardChanged),
     }
   }
 }
@@ -355,9 +368,25 @@ pub enum WindowEvent<'a> {
   /// hovered.
   HoveredFileCancelled,
 
+  /// A drag started with [`Window::start_drag`] has ended.
+  ///
+  /// [`Window::start_drag`]: crate::window::Window::start_drag
+  DragEnded(DropAction),
+
   /// The window received a unicode character.
   ReceivedImeText(String),
 
+  /// An event from the input method.
+  ///
+  /// The text committed by the input method is also reported with [`ReceivedImeText`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / iOS / Android:** Unsupported.
+  ///
+  /// [`ReceivedImeText`]: WindowEvent::ReceivedImeText
+  Ime(Ime),
+
   /// The window gained or lost focus.
   ///
   /// The parameter is true if the window has gained focus, and false if it has lost focus.
@@ -408,6 +437,11 @@ pub enum WindowEvent<'a> {
   CursorLeft { device_id: DeviceId },
 
   /// A mouse wheel movement or touchpad scroll occurred.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux (X11):** Touchpad scrolling is reported as a [`MouseScrollDelta::LineDelta`], in
+  ///   the scroll increments of the device.
   MouseWheel {
     device_id: DeviceId,
     delta: MouseScrollDelta,
@@ -446,6 +480,43 @@ pub enum WindowEvent<'a> {
   /// Touch event has been received
   Touch(Touch),
 
+  /// Pen (stylus) event has been received.
+  ///
+  /// The pen also moves the cursor, so it is reported as a mouse as well.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / iOS / Android:** Unsupported.
+  Pen(Pen),
+
+  /// Touchpad magnification event with two-finger pinch gesture.
+  ///
+  /// Positive delta values indicate magnification (zooming in) and negative delta values
+  /// indicate shrinking (zooming out).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / iOS / Android:** Unsupported.
+  TouchpadMagnify {
+    device_id: DeviceId,
+    delta: f64,
+    phase: TouchPhase,
+  },
+
+  /// Touchpad rotation event with two-finger rotation gesture.
+  ///
+  /// Positive delta values indicate rotation counterclockwise and negative delta values indicate
+  /// rotation clockwise, in degrees.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / iOS / Android:** Unsupported.
+  TouchpadRotate {
+    device_id: DeviceId,
+    delta: f32,
+    phase: TouchPhase,
+  },
+
   /// The window's scale factor has changed.
   ///
   /// The following user actions can cause DPI changes:
@@ -471,7 +542,8 @@ pub enum WindowEvent<'a> {
   ///
   /// ## Platform-specific
   ///
-  /// - **Linux / Android / iOS:** Unsupported
+  /// - **Linux:** Follows the GTK theme and the freedesktop `color-scheme` setting.
+  /// - **Android / iOS:** Unsupported
   ThemeChanged(Theme),
 
   /// The window decorations has been clicked.
@@ -493,7 +565,9 @@ impl Clone for WindowEvent<'static> {
       DroppedFile(file) => DroppedFile(file.clone()),
       HoveredFile(file) => HoveredFile(file.clone()),
       HoveredFileCancelled => HoveredFileCancelled,
+      DragEnded(action) => DragEnded(*action),
       ReceivedImeText(c) => ReceivedImeText(c.clone()),
+      Ime(ime) => Ime(ime.clone()),
       Focused(f) => Focused(*f),
       KeyboardInput {
         device_id,
@@ -565,6 +639,25 @@ impl Clone for WindowEvent<'static> {
         value: *value,
       },
       Touch(touch) => Touch(*touch),
+      Pen(pen) => Pen(*pen),
+      TouchpadMagnify {
+        device_id,
+        delta,
+        phase,
+      } => TouchpadMagnify {
+        device_id: *device_id,
+        delta: *delta,
+        phase: *phase,
+      },
+      TouchpadRotate {
+        device_id,
+        delta,
+        phase,
+      } => TouchpadRotate {
+        device_id: *device_id,
+        delta: *delta,
+        phase: *phase,
+      },
       ThemeChanged(theme) => ThemeChanged(*theme),
       ScaleFactorChanged { .. } => {
         unreachable!("Static event can't be about scale factor changing")
@@ -585,7 +678,9 @@ impl<'a> WindowEvent<'a> {
       DroppedFile(file) => Some(DroppedFile(file)),
       HoveredFile(file) => Some(HoveredFile(file)),
       HoveredFileCancelled => Some(HoveredFileCancelled),
+      DragEnded(action) => Some(DragEnded(action)),
       ReceivedImeText(c) => Some(ReceivedImeText(c)),
+      Ime(ime) => Some(Ime(ime)),
       Focused(focused) => Some(Focused(focused)),
       KeyboardInput {
         device_id,
@@ -652,6 +747,25 @@ impl<'a> WindowEvent<'a> {
         value,
       }),
       Touch(touch) => Some(Touch(touch)),
+      Pen(pen) => Some(Pen(pen)),
+      TouchpadMagnify {
+        device_id,
+        delta,
+        phase,
+      } => Some(TouchpadMagnify {
+        device_id,
+        delta,
+        phase,
+      }),
+      TouchpadRotate {
+        device_id,
+        delta,
+        phase,
+      } => Some(TouchpadRotate {
+        device_id,
+        delta,
+        phase,
+      }),
       ThemeChanged(theme) => Some(ThemeChanged(theme)),
       ScaleFactorChanged { .. } => None,
       DecorationsClick => Some(DecorationsClick),
@@ -686,7 +800,14 @@ impl DeviceId {
 /// window events typically arise from virtual devices (corresponding to GUI cursors and keyboard focus) the device IDs
 /// may not match.
 ///
-/// Note that these events are delivered regardless of input focus.
+/// By default, these events are also delivered while the application is unfocused. This is
+/// controlled by [`EventLoopWindowTarget::set_device_event_filter`].
+///
+/// [`EventLoopWindowTarget::set_device_event_filter`]: crate::event_loop::EventLoopWindowTarget::set_device_event_filter
+///
+/// ## Platform-specific
+///
+/// - **Linux:** Read from the XInput2 raw events, so only available on X11.
 #[non_exhaustive]
 #[derive(Clone, Debug, PartialEq)]
 pub enum DeviceEvent {
@@ -840,6 +961,29 @@ impl KeyEvent {
   }
 }
 
+/// Describes an event from the input method.
+///
+/// A composition starts with `Preedit` events, showing the text being composed, and ends with
+/// an empty `Preedit` followed by a `Commit` of the composed text, or just the empty `Preedit`
+/// if it was cancelled.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Ime {
+  /// The input method is enabled for the window, which will receive the other `Ime` events
+  /// until `Disabled`.
+  Enabled,
+  /// The text being composed changed.
+  ///
+  /// The second field is the cursor position in the text, as a range of byte indices. It is
+  /// `None` if the cursor shouldn't be shown.
+  Preedit(String, Option<(usize, usize)>),
+  /// The composed text is committed and should be inserted.
+  Commit(String),
+  /// The input method is disabled for the window, a composition in progress should be cleared.
+  Disabled,
+}
+
 /// Describes touch-screen input state.
 #[non_exhaustive]
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
@@ -914,12 +1058,57 @@ pub struct Touch {
   ///
   /// ## Platform-specific
   ///
-  /// - Only available on **iOS** 9.0+ and **Windows** 8+.
+  /// - Only available on **iOS** 9.0+, **Windows** 8+ and **Linux**.
   pub force: Option<Force>,
   /// Unique identifier of a finger.
   pub id: u64,
 }
 
+/// Represents a pen event.
+///
+/// When the pen comes close enough to the tablet, an `Entered` event is generated. It is followed
+/// by `Moved` events as it hovers, and by `Down` and `Up` events when it touches the tablet and
+/// is lifted. A `Left` event is generated once it is out of range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pen {
+  pub device_id: DeviceId,
+  pub phase: PenPhase,
+  pub tool: PenTool,
+  pub location: PhysicalPosition<f64>,
+  /// How hard the pen is pressed, between 0 and 1. May be `None` if the tablet does not support
+  /// pressure sensitivity.
+  pub pressure: Option<f64>,
+  /// The tilt of the pen along the x and y axes, between -1 and 1. May be `None` if the tablet
+  /// does not support it.
+  pub tilt: Option<(f64, f64)>,
+}
+
+/// Describes the state of a pen.
+#[non_exhaustive]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenPhase {
+  /// The pen came in range of the tablet.
+  Entered,
+  /// The pen touched the tablet.
+  Down,
+  /// The pen moved, or its pressure or tilt changed.
+  Moved,
+  /// The pen was lifted from the tablet.
+  Up,
+  /// The pen went out of range of the tablet.
+  Left,
+}
+
+/// Describes the end of the pen in use.
+#[non_exhaustive]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenTool {
+  Pen,
+  Eraser,
+}
+
 /// Describes the force of a touch event
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -211,6 +211,21 @@ impl<T> EventLoopWindowTarget<T> {
   pub fn primary_monitor(&self) -> Option<MonitorHandle> {
     self.p.primary_monitor()
   }
+
+  /// Changes when the [`DeviceEvent`]s are filtered out.
+  ///
+  /// The default is [`DeviceEventFilter::Never`], so the device events are also emitted while
+  /// the application is in the background.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Android:** Unsupported.
+  ///
+  /// [`DeviceEvent`]: crate::event::DeviceEvent
+  #[inline]
+  pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+    self.p.set_device_event_filter(filter);
+  }
 }
 
 unsafe impl<T> HasRawDisplayHandle for EventLoopWindowTarget<T> {
@@ -220,6 +235,25 @@ unsafe impl<T> HasRawDisplayHandle for EventLoopWindowTarget<T> {
   }
 }
 
+/// Filter controlling when the [`DeviceEvent`]s are emitted.
+///
+/// [`DeviceEvent`]: crate::event::DeviceEvent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeviceEventFilter {
+  /// Always filter out the device events.
+  Always,
+  /// Filter out the device events while no window of the application is focused.
+  Unfocused,
+  /// Never filter out the device events.
+  Never,
+}
+
+impl Default for DeviceEventFilter {
+  fn default() -> Self {
+    DeviceEventFilter::Never
+  }
+}
+
 /// Used to send custom events to `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
   event_loop_proxy: platform_impl::EventLoopProxy<T>,
@@ -32,6 +32,9 @@ pub enum BadIcon {
     width_x_height: usize,
     pixel_count: usize,
   },
+  /// Produced when the width or the height of an image is 0, where an image can't be empty.
+  #[non_exhaustive]
+  ZeroDimension { width: u32, height: u32 },
   /// Produced when underlying OS functionality failed to create the icon
   OsError(io::Error),
 }
@@ -52,6 +55,10 @@ impl fmt::Display for BadIcon {
                 "The specified dimensions ({:?}x{:?}) don't match the number of pixels supplied by the `rgba` argument ({:?}). For those dimensions, the expected pixel count is {:?}.",
                 width, height, pixel_count, width_x_height,
             ),
+            BadIcon::ZeroDimension { width, height } => write!(f,
+                "The specified dimensions ({:?}x{:?}) are empty, the width and the height must be greater than 0.",
+                width, height,
+            ),
             BadIcon::OsError(e) => write!(f, "OS error when instantiating the icon: {:?}", e),
         }
   }
@@ -105,6 +112,15 @@ mod constructors {
         })
       }
     }
+
+    /// Like [`RgbaIcon::from_rgba`], but also returns a `BadIcon` error if the width or the
+    /// height is 0, for the images which can't be empty.
+    pub fn from_non_empty_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+      if width == 0 || height == 0 {
+        return Err(BadIcon::ZeroDimension { width, height });
+      }
+      Self::from_rgba(rgba, width, height)
+    }
   }
 
   impl NoIcon {
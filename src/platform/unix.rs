@@ -9,20 +9,23 @@
   target_os = "openbsd"
 ))]
 
-use std::{os::raw::c_int, sync::Arc};
+use std::sync::Arc;
 
 // XConnection utilities
 #[doc(hidden)]
 pub use crate::platform_impl::x11;
 
-pub use crate::platform_impl::{hit_test, EventLoop as UnixEventLoop};
+pub use crate::platform_impl::{hit_test, ClipboardKind, EventLoop as UnixEventLoop};
 use crate::{
+  clipboard::Clipboard,
+  event::DeviceId,
   event_loop::{EventLoop, EventLoopWindowTarget},
-  platform_impl::{x11::xdisplay::XError, Parent},
+  platform_impl::Clipboard as UnixClipboard,
+  platform_impl::Parent,
   window::{Window, WindowBuilder},
 };
 
-use self::x11::xdisplay::XConnection;
+use self::x11::xdisplay::{x_error_callback, XConnection};
 
 /// Additional methods on `Window` that are specific to Unix.
 pub trait WindowExtUnix {
@@ -56,6 +59,17 @@ pub trait WindowBuilderExtUnix {
   /// For anyone who wants to draw the background themselves, set this to `false`.
   /// Default is `true`.
   fn with_transparent_draw(self, draw: bool) -> WindowBuilder;
+
+  /// Whether to register the window as a drop target for files.
+  ///
+  /// When enabled, dragging files over the window emits [`WindowEvent::HoveredFile`],
+  /// [`WindowEvent::HoveredFileCancelled`] and [`WindowEvent::DroppedFile`].
+  /// Set this to `false` to handle drag and drop yourself. Default is `true`.
+  ///
+  /// [`WindowEvent::HoveredFile`]: crate::event::WindowEvent::HoveredFile
+  /// [`WindowEvent::HoveredFileCancelled`]: crate::event::WindowEvent::HoveredFileCancelled
+  /// [`WindowEvent::DroppedFile`]: crate::event::WindowEvent::DroppedFile
+  fn with_drag_and_drop(self, drag_and_drop: bool) -> WindowBuilder;
 }
 
 impl WindowBuilderExtUnix for WindowBuilder {
@@ -73,6 +87,11 @@ impl WindowBuilderExtUnix for WindowBuilder {
     self.platform_specific.auto_transparent = draw;
     self
   }
+
+  fn with_drag_and_drop(mut self, drag_and_drop: bool) -> WindowBuilder {
+    self.platform_specific.drag_and_drop = drag_and_drop;
+    self
+  }
 }
 
 /// Additional methods on `EventLoop` that are specific to Unix.
@@ -100,6 +119,48 @@ impl<T> EventLoopExtUnix for EventLoop<T> {
   }
 }
 
+/// Additional methods on `Clipboard` that are specific to Unix.
+pub trait ClipboardExtUnix {
+  /// Creates a new `Clipboard` instance that reads from and writes to the given selection.
+  ///
+  /// Use [`ClipboardKind::Primary`] to implement select-to-copy and middle-click paste.
+  fn with_kind(kind: ClipboardKind) -> Self
+  where
+    Self: Sized;
+
+  /// The selection this `Clipboard` reads from and writes to.
+  fn kind(&self) -> ClipboardKind;
+}
+
+impl ClipboardExtUnix for Clipboard {
+  #[inline]
+  fn with_kind(kind: ClipboardKind) -> Self {
+    Clipboard(UnixClipboard::with_kind(kind))
+  }
+
+  #[inline]
+  fn kind(&self) -> ClipboardKind {
+    self.0.kind()
+  }
+}
+
+/// Additional methods on `DeviceId` that are specific to Unix.
+pub trait DeviceIdExtUnix {
+  /// Returns an identifier that persistently refers to this specific device, made of its vendor
+  /// id, product id and name.
+  ///
+  /// Will return `None` if the device is no longer available, doesn't report these, or if this
+  /// isn't called from the main thread.
+  fn persistent_identifier(&self) -> Option<String>;
+}
+
+impl DeviceIdExtUnix for DeviceId {
+  #[inline]
+  fn persistent_identifier(&self) -> Option<String> {
+    self.0.persistent_identifier()
+  }
+}
+
 /// Additional methods on `EventLoopWindowTarget` that are specific to Unix.
 pub trait EventLoopWindowTargetExtUnix {
   /// True if the `EventLoopWindowTarget` uses Wayland.
@@ -154,21 +215,3 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
   //     }
   // }
 }
-
-unsafe extern "C" fn x_error_callback(
-  _display: *mut x11::ffi::Display,
-  event: *mut x11::ffi::XErrorEvent,
-) -> c_int {
-  let error = XError {
-    // TODO get the error text as description
-    description: String::new(),
-    error_code: (*event).error_code,
-    request_code: (*event).request_code,
-    minor_code: (*event).minor_code,
-  };
-
-  error!("X11 error: {:#?}", error);
-
-  // Fun fact: this return value is completely ignored.
-  0
-}
@@ -479,6 +479,8 @@ pub struct EventLoopWindowTarget<T: 'static> {
 }
 
 impl<T: 'static> EventLoopWindowTarget<T> {
+  pub fn set_device_event_filter(&self, _filter: event_loop::DeviceEventFilter) {}
+
   pub fn primary_monitor(&self) -> Option<monitor::MonitorHandle> {
     Some(monitor::MonitorHandle {
       inner: MonitorHandle,
@@ -672,6 +674,16 @@ impl Window {
     ))
   }
 
+  pub fn start_drag(
+    &self,
+    _data: window::DragData,
+    _options: window::DragOptions,
+  ) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
   pub fn set_ignore_cursor_events(&self, _ignore: bool) -> Result<(), error::ExternalError> {
     Err(error::ExternalError::NotSupported(
       error::NotSupportedError::new(),
@@ -701,6 +713,8 @@ impl Window {
     ndk_glue::content_rect()
   }
 
+  pub fn set_theme(&self, _theme: Option<Theme>) {}
+
   pub fn theme(&self) -> Theme {
     Theme::Light
   }
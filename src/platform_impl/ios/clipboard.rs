@@ -1,6 +1,10 @@
 // Copyright 2019-2021 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
+use std::path::PathBuf;
+
+use crate::clipboard::{ClipboardFiles, ClipboardFormat, ClipboardImage, FileOperation};
+
 #[derive(Debug, Clone, Default)]
 pub struct Clipboard;
 impl Clipboard {
@@ -8,4 +12,34 @@ impl Clipboard {
   pub(crate) fn read_text(&self) -> Option<String> {
     None
   }
+  pub(crate) fn request_text<F: FnOnce(Option<String>) + Send + 'static>(&self, callback: F) {
+    callback(None)
+  }
+  pub(crate) fn put_formats(&mut self, _formats: &[ClipboardFormat]) {}
+  pub(crate) fn read_format(&self, _format: &str) -> Option<Vec<u8>> {
+    None
+  }
+  pub(crate) fn request_format<F: FnOnce(Option<Vec<u8>>) + Send + 'static>(
+    &self,
+    _format: &str,
+    callback: F,
+  ) {
+    callback(None)
+  }
+  pub(crate) fn store(&mut self) {}
+  pub(crate) fn available_formats(&self) -> Vec<String> {
+    Vec::new()
+  }
+  pub(crate) fn write_html(&mut self, _html: &str, _alt_text: &str) {}
+  pub(crate) fn read_html(&self) -> Option<String> {
+    None
+  }
+  pub(crate) fn write_image(&mut self, _image: &ClipboardImage) {}
+  pub(crate) fn read_image(&self) -> Option<ClipboardImage> {
+    None
+  }
+  pub(crate) fn write_files(&mut self, _paths: &[PathBuf], _operation: FileOperation) {}
+  pub(crate) fn read_files(&self) -> Option<ClipboardFiles> {
+    None
+  }
 }
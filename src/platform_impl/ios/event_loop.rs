@@ -16,7 +16,10 @@ use raw_window_handle::{RawDisplayHandle, UiKitDisplayHandle};
 use crate::{
   dpi::LogicalSize,
   event::Event,
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootEventLoopWindowTarget},
+  event_loop::{
+    ControlFlow, DeviceEventFilter, EventLoopClosed,
+    EventLoopWindowTarget as RootEventLoopWindowTarget,
+  },
   monitor::MonitorHandle as RootMonitorHandle,
   platform::ios::Idiom,
 };
@@ -58,6 +61,8 @@ pub struct EventLoopWindowTarget<T: 'static> {
 }
 
 impl<T: 'static> EventLoopWindowTarget<T> {
+  pub fn set_device_event_filter(&self, _filter: DeviceEventFilter) {}
+
   pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
     // guaranteed to be on main thread
     unsafe { monitor::uiscreens() }
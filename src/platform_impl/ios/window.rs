@@ -26,7 +26,8 @@ use crate::{
     monitor, view, EventLoopWindowTarget, Menu, MonitorHandle,
   },
   window::{
-    CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+    CursorIcon, DragData, DragOptions, Fullscreen, Theme, UserAttentionType, WindowAttributes,
+    WindowId as RootWindowId,
   },
 };
 
@@ -198,6 +199,10 @@ impl Inner {
     Err(ExternalError::NotSupported(NotSupportedError::new()))
   }
 
+  pub fn start_drag(&self, _data: DragData, _options: DragOptions) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
   pub fn set_ignore_cursor_events(&self, _ignore: bool) -> Result<(), ExternalError> {
     Err(ExternalError::NotSupported(NotSupportedError::new()))
   }
@@ -358,6 +363,8 @@ impl Inner {
     RawDisplayHandle::UiKit(UiKitDisplayHandle::empty())
   }
 
+  pub fn set_theme(&self, _theme: Option<Theme>) {}
+
   pub fn theme(&self) -> Theme {
     Theme::Light
   }
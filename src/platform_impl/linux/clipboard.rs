@@ -2,10 +2,35 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use gdk::Atom;
+use gdk_pixbuf::{Colorspace, Pixbuf};
+use glib::{translate::ToGlibPtr, MainContext};
 use gtk::{TargetEntry, TargetFlags};
 
+use std::path::PathBuf;
+
+use crate::clipboard::{ClipboardFiles, ClipboardFormat, ClipboardImage, FileOperation, FormatId};
+
+/// The selection a [`Clipboard`](crate::clipboard::Clipboard) reads from and writes to.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClipboardKind {
+  /// The `CLIPBOARD` selection, used by explicit copy and paste actions.
+  Clipboard,
+  /// The `PRIMARY` selection, which holds the last selected text and is usually pasted with a
+  /// middle click.
+  Primary,
+}
+
+impl Default for ClipboardKind {
+  fn default() -> Self {
+    ClipboardKind::Clipboard
+  }
+}
+
 #[derive(Debug, Clone, Default)]
-pub struct Clipboard;
+pub struct Clipboard {
+  kind: ClipboardKind,
+}
 
 const CLIPBOARD_TARGETS: [&str; 5] = [
   "UTF8_STRING",
@@ -15,27 +40,27 @@ const CLIPBOARD_TARGETS: [&str; 5] = [
   "text/plain",
 ];
 
-impl Clipboard {
-  pub(crate) fn write_text(&mut self, string: impl AsRef<str>) {
-    let string = string.as_ref().to_string();
+const HTML: FormatId = "text/html";
+const GNOME_COPIED_FILES: FormatId = "x-special/gnome-copied-files";
+const KDE_CUT_SELECTION: FormatId = "application/x-kde-cutselection";
+const URI_LIST: FormatId = "text/uri-list";
 
-    let display = gdk::Display::default().unwrap();
-    let clipboard = gtk::Clipboard::default(&display).unwrap();
+impl Clipboard {
+  pub(crate) fn with_kind(kind: ClipboardKind) -> Self {
+    Self { kind }
+  }
 
-    let targets: Vec<TargetEntry> = CLIPBOARD_TARGETS
-      .iter()
-      .enumerate()
-      .map(|(i, target)| TargetEntry::new(target, TargetFlags::all(), i as u32))
-      .collect();
+  pub(crate) fn kind(&self) -> ClipboardKind {
+    self.kind
+  }
 
-    clipboard.set_with_data(&targets, move |_, selection, _| {
-      selection.set(&selection.target(), 8i32, string.as_bytes());
-    });
+  pub(crate) fn write_text(&mut self, string: impl AsRef<str>) {
+    let format: ClipboardFormat = string.as_ref().into();
+    self.put_formats(&[format]);
   }
 
   pub(crate) fn read_text(&self) -> Option<String> {
-    let display = gdk::Display::default().unwrap();
-    let clipboard = gtk::Clipboard::default(&display).unwrap();
+    let clipboard = self.gtk_clipboard();
 
     for target in &CLIPBOARD_TARGETS {
       let atom = Atom::intern(target);
@@ -46,4 +71,262 @@ impl Clipboard {
 
     None
   }
+
+  pub(crate) fn request_text<F: FnOnce(Option<String>) + Send + 'static>(&self, callback: F) {
+    let kind = self.kind;
+    // GTK can only be used from the main context, so the request is sent there instead of
+    // blocking the calling thread with `wait_for_contents`.
+    MainContext::default().invoke(move || {
+      Clipboard::with_kind(kind)
+        .gtk_clipboard()
+        .request_text(move |_, text| callback(text.map(ToString::to_string)));
+    });
+  }
+
+  pub(crate) fn put_formats(&mut self, formats: &[ClipboardFormat]) {
+    let clipboard = self.gtk_clipboard();
+
+    // The target info is the index of the format to hand out when the target is requested.
+    let mut targets: Vec<TargetEntry> = Vec::new();
+    for (i, format) in formats.iter().enumerate() {
+      if format.identifier == ClipboardFormat::TEXT {
+        targets.extend(
+          CLIPBOARD_TARGETS
+            .iter()
+            .map(|target| TargetEntry::new(target, TargetFlags::all(), i as u32)),
+        );
+      } else {
+        targets.push(TargetEntry::new(
+          format.identifier,
+          TargetFlags::all(),
+          i as u32,
+        ));
+      }
+    }
+
+    let formats = formats.to_vec();
+    clipboard.set_with_data(&targets, move |_, selection, info| {
+      if let Some(format) = formats.get(info as usize) {
+        selection.set(&selection.target(), 8i32, &format.data);
+      }
+    });
+  }
+
+  pub(crate) fn read_format(&self, format: &str) -> Option<Vec<u8>> {
+    if format == ClipboardFormat::TEXT {
+      return self.read_text().map(String::into_bytes);
+    }
+
+    let atom = Atom::intern(format);
+    self
+      .gtk_clipboard()
+      .wait_for_contents(&atom)
+      .map(|selection| selection.data())
+  }
+
+  pub(crate) fn request_format<F: FnOnce(Option<Vec<u8>>) + Send + 'static>(
+    &self,
+    format: &str,
+    callback: F,
+  ) {
+    if format == ClipboardFormat::TEXT {
+      return self.request_text(move |text| callback(text.map(String::into_bytes)));
+    }
+
+    let (kind, format) = (self.kind, format.to_string());
+    MainContext::default().invoke(move || {
+      let atom = Atom::intern(&format);
+      Clipboard::with_kind(kind)
+        .gtk_clipboard()
+        .request_contents(&atom, move |_, selection| {
+          // A negative length means the content couldn't be retrieved.
+          if selection.length() < 0 {
+            callback(None);
+          } else {
+            callback(Some(selection.data()));
+          }
+        });
+    });
+  }
+
+  pub(crate) fn store(&mut self) {
+    let clipboard = self.gtk_clipboard();
+    // gtk-rs doesn't bind `gtk_clipboard_set_can_store`. An empty list of targets allows the
+    // clipboard manager to store all of them.
+    unsafe {
+      gtk::ffi::gtk_clipboard_set_can_store(clipboard.to_glib_none().0, std::ptr::null(), 0);
+    }
+    clipboard.store();
+  }
+
+  pub(crate) fn available_formats(&self) -> Vec<String> {
+    self
+      .gtk_clipboard()
+      .wait_for_targets()
+      .unwrap_or_default()
+      .iter()
+      .map(|atom| atom.name().to_string())
+      .collect()
+  }
+
+  pub(crate) fn write_html(&mut self, html: &str, alt_text: &str) {
+    self.put_formats(&[ClipboardFormat::new(HTML, html), alt_text.into()]);
+  }
+
+  pub(crate) fn read_html(&self) -> Option<String> {
+    let data = self.read_format(HTML)?;
+    // Some browsers, Firefox in particular, offer `text/html` as UTF-16.
+    match data.as_slice() {
+      [0xFF, 0xFE, rest @ ..] => {
+        let utf16: Vec<u16> = rest
+          .chunks_exact(2)
+          .map(|c| u16::from_le_bytes([c[0], c[1]]))
+          .collect();
+        String::from_utf16(&utf16).ok()
+      }
+      _ => String::from_utf8(data).ok(),
+    }
+  }
+
+  pub(crate) fn write_image(&mut self, image: &ClipboardImage) {
+    let pixbuf = Pixbuf::from_mut_slice(
+      image.rgba.clone(),
+      Colorspace::Rgb,
+      true,
+      8,
+      image.width as i32,
+      image.height as i32,
+      image.width as i32 * 4,
+    );
+    self.gtk_clipboard().set_image(&pixbuf);
+  }
+
+  pub(crate) fn read_image(&self) -> Option<ClipboardImage> {
+    let pixbuf = self.gtk_clipboard().wait_for_image()?;
+    let pixbuf = if pixbuf.has_alpha() {
+      pixbuf
+    } else {
+      pixbuf.add_alpha(false, 0, 0, 0)?
+    };
+    if pixbuf.bits_per_sample() != 8 {
+      return None;
+    }
+
+    let width = pixbuf.width() as usize;
+    let height = pixbuf.height() as usize;
+    let row_stride = pixbuf.rowstride() as usize;
+    let bytes = pixbuf.read_pixel_bytes()?;
+
+    // Rows may be padded, so copy them one by one into a tightly packed buffer.
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in 0..height {
+      let start = row * row_stride;
+      rgba.extend_from_slice(bytes.get(start..start + width * 4)?);
+    }
+
+    Some(ClipboardImage {
+      rgba,
+      width: width as u32,
+      height: height as u32,
+    })
+  }
+
+  pub(crate) fn write_files(&mut self, paths: &[PathBuf], operation: FileOperation) {
+    let uris: Vec<String> = paths
+      .iter()
+      .filter_map(|path| glib::filename_to_uri(path, None).ok())
+      .map(|uri| uri.to_string())
+      .collect();
+    let (gnome_operation, kde_cut) = match operation {
+      FileOperation::Copy => ("copy", "0"),
+      FileOperation::Cut => ("cut", "1"),
+    };
+    let text = paths
+      .iter()
+      .map(|path| path.to_string_lossy())
+      .collect::<Vec<_>>()
+      .join("\n");
+
+    self.put_formats(&[
+      ClipboardFormat::new(
+        GNOME_COPIED_FILES,
+        format!("{}\n{}", gnome_operation, uris.join("\n")),
+      ),
+      ClipboardFormat::new(URI_LIST, format!("{}\r\n", uris.join("\r\n"))),
+      ClipboardFormat::new(KDE_CUT_SELECTION, kde_cut),
+      text.into(),
+    ]);
+  }
+
+  pub(crate) fn read_files(&self) -> Option<ClipboardFiles> {
+    if let Some(files) = self
+      .read_format(GNOME_COPIED_FILES)
+      .and_then(|data| parse_gnome_copied_files(&data))
+    {
+      return Some(files);
+    }
+
+    let paths = parse_uri_list(&self.read_format(URI_LIST)?)?;
+    let operation = match self.read_format(KDE_CUT_SELECTION).as_deref() {
+      Some(b"1") => FileOperation::Cut,
+      _ => FileOperation::Copy,
+    };
+    Some(ClipboardFiles { paths, operation })
+  }
+
+  fn gtk_clipboard(&self) -> gtk::Clipboard {
+    let display = gdk::Display::default().unwrap();
+    let selection = match self.kind {
+      ClipboardKind::Clipboard => gdk::SELECTION_CLIPBOARD,
+      ClipboardKind::Primary => gdk::SELECTION_PRIMARY,
+    };
+    gtk::Clipboard::for_display(&display, &selection)
+  }
+}
+
+/// Parses the content of `x-special/gnome-copied-files`, the operation followed by one URI per line.
+fn parse_gnome_copied_files(data: &[u8]) -> Option<ClipboardFiles> {
+  let data = std::str::from_utf8(data).ok()?;
+  let (operation, uris) = data.split_once('\n')?;
+  let operation = match operation.trim() {
+    "copy" => FileOperation::Copy,
+    "cut" => FileOperation::Cut,
+    _ => return None,
+  };
+  Some(ClipboardFiles {
+    paths: parse_uri_list(uris.as_bytes())?,
+    operation,
+  })
+}
+
+/// Parses a `text/uri-list` as described in RFC 2483, keeping only local files.
+fn parse_uri_list(data: &[u8]) -> Option<Vec<PathBuf>> {
+  let paths: Vec<PathBuf> = std::str::from_utf8(data)
+    .ok()?
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .filter_map(|uri| glib::filename_from_uri(uri).ok())
+    .map(|(path, _)| path)
+    .collect();
+  if paths.is_empty() {
+    None
+  } else {
+    Some(paths)
+  }
+}
+
+#[test]
+fn parse_copied_files() {
+  let files = parse_gnome_copied_files(b"cut\nfile:///tmp/a%20b.txt\nfile:///tmp/c").unwrap();
+  assert_eq!(files.operation, FileOperation::Cut);
+  assert_eq!(
+    files.paths,
+    vec![PathBuf::from("/tmp/a b.txt"), PathBuf::from("/tmp/c")]
+  );
+
+  let paths = parse_uri_list(b"# comment\r\nfile:///tmp/a\r\nhttps://example.com\r\n").unwrap();
+  assert_eq!(paths, vec![PathBuf::from("/tmp/a")]);
+
+  assert!(parse_gnome_copied_files(b"link\nfile:///tmp/a").is_none());
 }
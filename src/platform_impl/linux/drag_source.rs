@@ -0,0 +1,133 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cell::RefCell, rc::Rc};
+
+use crossbeam_channel::Sender;
+use gdk::DragAction;
+use gdk_pixbuf::{Colorspace, Pixbuf};
+use glib::SignalHandlerId;
+use gtk::{prelude::*, Inhibit, TargetList};
+
+use crate::{
+  event::{Event, WindowEvent},
+  window::{DragData, DragImage, DragOptions, DropAction, WindowId as RootWindowId},
+};
+
+use super::window::WindowId;
+
+/// Starts dragging `data` out of `window` and emits `DragEnded` once the drag is over.
+pub(crate) fn start_drag<T: 'static>(
+  window: &gtk::Window,
+  id: WindowId,
+  data: DragData,
+  options: DragOptions,
+  tx: Sender<Event<'static, T>>,
+) {
+  let targets = TargetList::new(&[]);
+  match &data {
+    DragData::Files(_) => targets.add_uri_targets(0),
+    DragData::Text(_) => targets.add_text_targets(0),
+    DragData::Image(_) => targets.add_image_targets(0, true),
+  }
+
+  // Only one drag can be in progress at a time, so the handlers below are connected for the
+  // duration of this drag and disconnected once it ends.
+  let handlers: Rc<RefCell<Vec<SignalHandlerId>>> = Default::default();
+  let failed = Rc::new(RefCell::new(false));
+
+  handlers.borrow_mut().push(window.connect_drag_data_get(
+    move |_, _, selection, _, _| match &data {
+      DragData::Files(paths) => {
+        let uris: Vec<String> = paths
+          .iter()
+          .filter_map(|path| glib::filename_to_uri(path, None).ok())
+          .map(|uri| uri.to_string())
+          .collect();
+        let uris: Vec<&str> = uris.iter().map(String::as_str).collect();
+        selection.set_uris(&uris);
+      }
+      DragData::Text(text) => {
+        selection.set_text(text);
+      }
+      DragData::Image(DragImage(image)) => {
+        let pixbuf = Pixbuf::from_mut_slice(
+          image.rgba.clone(),
+          Colorspace::Rgb,
+          true,
+          8,
+          image.width as i32,
+          image.height as i32,
+          image.width as i32 * 4,
+        );
+        selection.set_pixbuf(&pixbuf);
+      }
+    },
+  ));
+
+  let failed_ = failed.clone();
+  handlers
+    .borrow_mut()
+    .push(window.connect_drag_failed(move |_, _, _| {
+      *failed_.borrow_mut() = true;
+      Inhibit(false)
+    }));
+
+  let handlers_ = handlers.clone();
+  let tx_ = tx.clone();
+  handlers
+    .borrow_mut()
+    .push(window.connect_drag_end(move |window, context| {
+      // `drag-failed` is emitted before `drag-end` when the drag didn't succeed.
+      let action = if *failed.borrow() {
+        DropAction::Cancel
+      } else {
+        drop_action(context.selected_action())
+      };
+      send_drag_ended(&tx_, id, action);
+
+      for handler in handlers_.borrow_mut().drain(..) {
+        window.disconnect(handler);
+      }
+    }));
+
+  let actions = if options.allow_move {
+    DragAction::COPY | DragAction::MOVE
+  } else {
+    DragAction::COPY
+  };
+  // Without an event, GTK starts the drag at the current pointer position.
+  match window.drag_begin_with_coordinates(&targets, actions, 1, None, -1, -1) {
+    Some(context) => {
+      if let Some(icon) = options.icon {
+        let pixbuf: Pixbuf = icon.inner.into();
+        context.drag_set_icon_pixbuf(&pixbuf, pixbuf.width() / 2, pixbuf.height() / 2);
+      }
+    }
+    None => {
+      for handler in handlers.borrow_mut().drain(..) {
+        window.disconnect(handler);
+      }
+      send_drag_ended(&tx, id, DropAction::Cancel);
+    }
+  }
+}
+
+fn drop_action(action: DragAction) -> DropAction {
+  if action.contains(DragAction::MOVE) {
+    DropAction::Move
+  } else if action.contains(DragAction::COPY) {
+    DropAction::Copy
+  } else {
+    DropAction::Cancel
+  }
+}
+
+fn send_drag_ended<T>(tx: &Sender<Event<'static, T>>, id: WindowId, action: DropAction) {
+  if let Err(e) = tx.send(Event::WindowEvent {
+    window_id: RootWindowId(id),
+    event: WindowEvent::DragEnded(action),
+  }) {
+    log::warn!("Failed to send drag ended event to event channel: {}", e);
+  }
+}
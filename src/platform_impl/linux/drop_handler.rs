@@ -0,0 +1,149 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use crossbeam_channel::Sender;
+use gdk::{Atom, DragAction, DragContext};
+use gtk::{prelude::*, DestDefaults, SelectionData, TargetEntry, TargetFlags};
+
+use crate::{
+  event::{Event, WindowEvent},
+  window::WindowId as RootWindowId,
+};
+
+use super::window::WindowId;
+
+const URI_LIST: &str = "text/uri-list";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DropState {
+  /// No drag is over the window.
+  Idle,
+  /// A drag entered the window and its content has been requested.
+  Requested,
+  /// The content of the drag has been received. `valid` is `false` if it doesn't hold any file.
+  Hovered { valid: bool },
+  /// A drag holding files left the window, waiting to see whether it is followed by a drop.
+  Leaving,
+  /// The drag has been dropped and its content has been requested.
+  Dropped,
+}
+
+/// Registers `window` as a drag destination for files and emits `HoveredFile`, `DroppedFile`
+/// and `HoveredFileCancelled`, the same way the Windows `FileDropHandler` does.
+pub(crate) fn connect_drop_handler<T: 'static>(
+  window: &gtk::Window,
+  id: WindowId,
+  tx: Sender<Event<'static, T>>,
+) {
+  window.drag_dest_set(
+    DestDefaults::empty(),
+    &[TargetEntry::new(URI_LIST, TargetFlags::empty(), 0)],
+    DragAction::COPY,
+  );
+
+  let state = Rc::new(RefCell::new(DropState::Idle));
+  let send = Rc::new(move |event: WindowEvent<'static>| {
+    if let Err(e) = tx.send(Event::WindowEvent {
+      window_id: RootWindowId(id),
+      event,
+    }) {
+      log::warn!("Failed to send file drop event to event channel: {}", e);
+    }
+  });
+
+  let state_ = state.clone();
+  window.connect_drag_motion(move |window, context, _, _, time| {
+    let current = *state_.borrow();
+    match current {
+      DropState::Idle => {
+        // The status is reported once the content of the drag is known. The state must not be
+        // borrowed here, the data may be delivered right away when dragging within the app.
+        *state_.borrow_mut() = DropState::Requested;
+        window.drag_get_data(context, &Atom::intern(URI_LIST), time);
+      }
+      DropState::Hovered { valid } => report_status(context, valid, time),
+      // The drag came back before the leave was processed, nothing was cancelled.
+      DropState::Leaving => {
+        *state_.borrow_mut() = DropState::Hovered { valid: true };
+        report_status(context, true, time);
+      }
+      DropState::Requested | DropState::Dropped => (),
+    }
+    true
+  });
+
+  let state_ = state.clone();
+  let send_ = send.clone();
+  window.connect_drag_data_received(move |_, context, _, _, selection, _, time| {
+    let mut state = state_.borrow_mut();
+    match *state {
+      DropState::Requested => {
+        let paths = selection_paths(selection);
+        let valid = !paths.is_empty();
+        for path in paths {
+          send_(WindowEvent::HoveredFile(path));
+        }
+        *state = DropState::Hovered { valid };
+        report_status(context, valid, time);
+      }
+      DropState::Dropped => {
+        let paths = selection_paths(selection);
+        context.drag_finish(!paths.is_empty(), false, time);
+        for path in paths {
+          send_(WindowEvent::DroppedFile(path));
+        }
+        *state = DropState::Idle;
+      }
+      _ => (),
+    }
+  });
+
+  let state_ = state.clone();
+  window.connect_drag_leave(move |_, _, _| {
+    let mut state = state_.borrow_mut();
+    if *state != (DropState::Hovered { valid: true }) {
+      *state = DropState::Idle;
+      return;
+    }
+
+    // GTK emits `drag-leave` right before `drag-drop`, so the cancellation is only sent if no
+    // drop follows.
+    *state = DropState::Leaving;
+    let state = state_.clone();
+    let send = send.clone();
+    glib::idle_add_local_once(move || {
+      let mut state = state.borrow_mut();
+      if *state == DropState::Leaving {
+        *state = DropState::Idle;
+        send(WindowEvent::HoveredFileCancelled);
+      }
+    });
+  });
+
+  window.connect_drag_drop(move |window, context, _, _, time| {
+    *state.borrow_mut() = DropState::Dropped;
+    window.drag_get_data(context, &Atom::intern(URI_LIST), time);
+    true
+  });
+}
+
+fn report_status(context: &DragContext, valid: bool, time: u32) {
+  let action = if valid {
+    DragAction::COPY
+  } else {
+    DragAction::empty()
+  };
+  context.drag_status(action, time);
+}
+
+/// The local files of a `text/uri-list` selection.
+fn selection_paths(selection: &SelectionData) -> Vec<PathBuf> {
+  selection
+    .uris()
+    .iter()
+    .filter_map(|uri| glib::filename_from_uri(uri).ok())
+    .map(|(path, _)| path)
+    .collect()
+}
@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-  cell::RefCell,
-  collections::{HashSet, VecDeque},
+  cell::{Cell, RefCell},
+  collections::{HashMap, HashSet, VecDeque},
   error::Error,
   process,
   rc::Rc,
@@ -12,30 +12,35 @@ use std::{
 };
 
 use cairo::{RectangleInt, Region};
-use gdk::{Cursor, CursorType, EventKey, EventMask, ScrollDirection, WindowEdge, WindowState};
+use gdk::{
+  AxisUse, Cursor, CursorType, EventKey, EventMask, EventType, ScrollDirection, WindowEdge,
+  WindowState,
+};
 use gio::{prelude::*, Cancellable};
-use glib::{source::Priority, Continue, MainContext};
+use glib::{source::Priority, translate::ToGlibPtr, Continue, MainContext};
 use gtk::{builders::AboutDialogBuilder, prelude::*, Inhibit};
 
 use raw_window_handle::{RawDisplayHandle, XlibDisplayHandle};
 
 use crate::{
   accelerator::AcceleratorId,
-  dpi::{LogicalPosition, LogicalSize},
+  dpi::{LogicalPosition, LogicalSize, PhysicalSize},
   event::{
-    ElementState, Event, MouseButton, MouseScrollDelta, StartCause, TouchPhase, WindowEvent,
+    DeviceEvent, DeviceId as RootDeviceId, ElementState, Event, Force, MouseButton,
+    MouseScrollDelta, StartCause, Touch, TouchPhase, WindowEvent,
   },
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
+  event_loop::{ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW},
   keyboard::ModifiersState,
   menu::{MenuItem, MenuType},
   monitor::MonitorHandle as RootMonitorHandle,
-  platform_impl::platform::{window::hit_test, DEVICE_ID},
+  platform_impl::platform::{device_id, window::hit_test, DeviceId},
   window::{CursorIcon, Fullscreen, WindowId as RootWindowId},
 };
 
 use super::{
-  keyboard,
+  drag_source, drop_handler, ime, keyboard,
   monitor::MonitorHandle,
+  pen, raw_input, theme,
   window::{WindowId, WindowRequest},
 };
 
@@ -49,10 +54,17 @@ pub struct EventLoopWindowTarget<T> {
   pub(crate) windows: Rc<RefCell<HashSet<WindowId>>>,
   /// Window requests sender
   pub(crate) window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+  /// When the device events are filtered out
+  pub(crate) device_event_filter: Rc<Cell<DeviceEventFilter>>,
   _marker: std::marker::PhantomData<T>,
 }
 
 impl<T> EventLoopWindowTarget<T> {
+  #[inline]
+  pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+    self.device_event_filter.set(filter);
+  }
+
   #[inline]
   pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
     let mut handles = VecDeque::new();
@@ -102,6 +114,9 @@ pub struct EventLoop<T: 'static> {
   events: crossbeam_channel::Receiver<Event<'static, T>>,
   /// Draw queue of EventLoop
   draws: crossbeam_channel::Receiver<WindowId>,
+  /// Scale factor changes of the windows, with the suggested new inner size. They are kept apart
+  /// from the events since `ScaleFactorChanged` borrows the new inner size.
+  scale_factor_changes: crossbeam_channel::Receiver<(WindowId, f64, PhysicalSize<u32>)>,
 }
 
 impl<T: 'static> EventLoop<T> {
@@ -127,6 +142,7 @@ impl<T: 'static> EventLoop<T> {
     // Send StartCause::Init event
     let (event_tx, event_rx) = crossbeam_channel::unbounded();
     let (draw_tx, draw_rx) = crossbeam_channel::unbounded();
+    let (scale_factor_tx, scale_factor_rx) = crossbeam_channel::unbounded();
     let event_tx_ = event_tx.clone();
     app.connect_activate(move |_| {
       if let Err(e) = event_tx_.send(Event::NewEvents(StartCause::Init)) {
@@ -139,11 +155,59 @@ impl<T: 'static> EventLoop<T> {
     let window_requests_tx_ = window_requests_tx.clone();
     let display = gdk::Display::default()
       .expect("GdkDisplay not found. This usually means `gkt_init` hasn't called yet.");
+
+    // Notify the clipboard changes
+    let event_tx_ = event_tx.clone();
+    gtk::Clipboard::for_display(&display, &gdk::SELECTION_CLIPBOARD).connect_local(
+      "owner-change",
+      false,
+      move |_| {
+        if let Err(e) = event_tx_.send(Event::ClipboardChanged) {
+          log::warn!(
+            "Failed to send clipboard changed event to event channel: {}",
+            e
+          );
+        }
+        None
+      },
+    );
+
+    // Raw device events are only available through XInput2.
+    let device_event_filter = Rc::new(Cell::new(DeviceEventFilter::default()));
+    if !display.backend().is_wayland() {
+      raw_input::connect_raw_input(&app, device_event_filter.clone(), event_tx.clone());
+    }
+
+    if let Some(seat) = display.default_seat() {
+      let (filter, tx) = (device_event_filter.clone(), event_tx.clone());
+      seat.connect_device_added(move |_, device| {
+        send_device_event(&filter, &tx, device, DeviceEvent::Added);
+      });
+      let (filter, tx) = (device_event_filter.clone(), event_tx.clone());
+      seat.connect_device_removed(move |_, device| {
+        send_device_event(&filter, &tx, device, DeviceEvent::Removed);
+      });
+    }
+
+    let windows = Rc::new(RefCell::new(HashSet::new()));
+    let (windows_, tx) = (windows.clone(), event_tx.clone());
+    theme::connect_theme_changed(move |theme| {
+      for id in windows_.borrow().iter() {
+        if let Err(e) = tx.send(Event::WindowEvent {
+          window_id: RootWindowId(*id),
+          event: WindowEvent::ThemeChanged(theme),
+        }) {
+          log::warn!("Failed to send theme changed event to event channel: {}", e);
+        }
+      }
+    });
+
     let window_target = EventLoopWindowTarget {
       display,
       app,
-      windows: Rc::new(RefCell::new(HashSet::new())),
+      windows: windows.clone(),
       window_requests_tx,
+      device_event_filter,
       _marker: std::marker::PhantomData,
     };
 
@@ -157,6 +221,9 @@ impl<T: 'static> EventLoop<T> {
       Continue(true)
     });
 
+    // The input method context of each window
+    let imes: Rc<RefCell<HashMap<WindowId, gtk::IMMulticontext>>> = Default::default();
+
     // Window Request
     window_requests_rx.attach(Some(&context), move |(id, request)| {
       if let Some(window) = app_.window_by_id(id.0) {
@@ -243,6 +310,9 @@ impl<T: 'static> EventLoop<T> {
               window.begin_move_drag(1, x, y, 0);
             }
           }
+          WindowRequest::StartDrag(data, options) => {
+            drag_source::start_drag(&window, id, data, options, event_tx.clone());
+          }
           WindowRequest::Fullscreen(fullscreen) => match fullscreen {
             Some(f) => {
               if let Fullscreen::Borderless(m) = f {
@@ -350,15 +420,27 @@ impl<T: 'static> EventLoop<T> {
               window.input_shape_combine_region(None)
             };
           }
-          WindowRequest::WireUpEvents { transparent } => {
+          WindowRequest::ImePosition((x, y)) => {
+            if let Some(ime) = imes.borrow().get(&id) {
+              ime.set_cursor_location(&gdk::Rectangle::new(x, y, 0, 0));
+            }
+          }
+          WindowRequest::WireUpEvents {
+            transparent,
+            drag_and_drop,
+          } => {
             window.add_events(
               EventMask::POINTER_MOTION_MASK
                 | EventMask::BUTTON1_MOTION_MASK
                 | EventMask::BUTTON_PRESS_MASK
                 | EventMask::TOUCH_MASK
+                | EventMask::TOUCHPAD_GESTURE_MASK
                 | EventMask::STRUCTURE_MASK
                 | EventMask::FOCUS_CHANGE_MASK
-                | EventMask::SCROLL_MASK,
+                | EventMask::SCROLL_MASK
+                | EventMask::SMOOTH_SCROLL_MASK
+                | EventMask::PROXIMITY_IN_MASK
+                | EventMask::PROXIMITY_OUT_MASK,
             );
 
             // Allow resizing unmaximized borderless window
@@ -434,6 +516,85 @@ impl<T: 'static> EventLoop<T> {
               Inhibit(false)
             });
 
+            let tx_clone = event_tx.clone();
+            window.connect_touch_event(move |window, event| {
+              let phase = match event.event_type() {
+                EventType::TouchBegin => TouchPhase::Started,
+                EventType::TouchUpdate => TouchPhase::Moved,
+                EventType::TouchEnd => TouchPhase::Ended,
+                EventType::TouchCancel => TouchPhase::Cancelled,
+                _ => return Inhibit(false),
+              };
+              if let (Some(sequence), Some((x, y))) = (event.event_sequence(), event.coords()) {
+                let scale_factor = window.scale_factor() as f64;
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::Touch(Touch {
+                    device_id: device_id(event),
+                    phase,
+                    location: LogicalPosition::new(x, y).to_physical(scale_factor),
+                    // GDK normalizes the pressure between 0 and 1.
+                    force: event.axis(AxisUse::Pressure).map(Force::Normalized),
+                    // The sequence is an opaque pointer which stays the same until the touch ends.
+                    id: sequence.to_glib_none().0 as u64,
+                  }),
+                }) {
+                  log::warn!("Failed to send touch event to event channel: {}", e);
+                }
+              }
+              Inhibit(false)
+            });
+
+            let tx_clone = event_tx.clone();
+            // GDK reports the scale relative to the start of the pinch.
+            let pinch_scale = Cell::new(1.0);
+            window.connect_event(move |_, event| {
+              let pinch = match event.downcast_ref::<gdk::EventTouchpadPinch>() {
+                Some(pinch) => pinch,
+                None => return Inhibit(false),
+              };
+              let raw: &gdk_sys::GdkEventTouchpadPinch = pinch.as_ref();
+              let phase = match raw.phase as gdk_sys::GdkTouchpadGesturePhase {
+                gdk_sys::GDK_TOUCHPAD_GESTURE_PHASE_BEGIN => {
+                  pinch_scale.set(1.0);
+                  TouchPhase::Started
+                }
+                gdk_sys::GDK_TOUCHPAD_GESTURE_PHASE_UPDATE => TouchPhase::Moved,
+                gdk_sys::GDK_TOUCHPAD_GESTURE_PHASE_END => TouchPhase::Ended,
+                _ => TouchPhase::Cancelled,
+              };
+              // The scale is reset to 1 when the pinch ends or is cancelled, which would undo
+              // the magnification.
+              let delta = match phase {
+                TouchPhase::Ended | TouchPhase::Cancelled => 0.0,
+                _ => pinch.scale() - pinch_scale.replace(pinch.scale()),
+              };
+              let device_id = device_id(event);
+              let magnify = WindowEvent::TouchpadMagnify {
+                device_id,
+                delta,
+                phase,
+              };
+              // GDK uses positive angles for clockwise rotations, in radians.
+              let rotate = WindowEvent::TouchpadRotate {
+                device_id,
+                delta: -pinch.angle_delta().to_degrees() as f32,
+                phase,
+              };
+              for event in [magnify, rotate] {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event,
+                }) {
+                  log::warn!(
+                    "Failed to send touchpad gesture event to event channel: {}",
+                    e
+                  );
+                }
+              }
+              Inhibit(false)
+            });
+
             let tx_clone = event_tx.clone();
             window.connect_delete_event(move |_, _| {
               if let Err(e) = tx_clone.send(Event::WindowEvent {
@@ -503,7 +664,10 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
+            let (imes_, windows_) = (imes.clone(), windows.clone());
             window.connect_destroy(move |_| {
+              imes_.borrow_mut().remove(&id);
+              windows_.borrow_mut().remove(&id);
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Destroyed,
@@ -516,11 +680,11 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
-            window.connect_enter_notify_event(move |_, _| {
+            window.connect_enter_notify_event(move |_, event| {
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::CursorEntered {
-                  device_id: DEVICE_ID,
+                  device_id: device_id(event),
                 },
               }) {
                 log::warn!(
@@ -540,7 +704,7 @@ impl<T: 'static> EventLoop<T> {
                   window_id: RootWindowId(id),
                   event: WindowEvent::CursorMoved {
                     position: LogicalPosition::new(x, y).to_physical(scale_factor as f64),
-                    device_id: DEVICE_ID,
+                    device_id: device_id(motion),
                     // this field is depracted so it is fine to pass empty state
                     modifiers: ModifiersState::empty(),
                   },
@@ -552,11 +716,11 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
-            window.connect_leave_notify_event(move |_, _| {
+            window.connect_leave_notify_event(move |_, event| {
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::CursorLeft {
-                  device_id: DEVICE_ID,
+                  device_id: device_id(event),
                 },
               }) {
                 log::warn!("Failed to send cursor left event to event channel: {}", e);
@@ -577,7 +741,7 @@ impl<T: 'static> EventLoop<T> {
                     _ => MouseButton::Other(button as u16),
                   },
                   state: ElementState::Pressed,
-                  device_id: DEVICE_ID,
+                  device_id: device_id(event),
                   // this field is depracted so it is fine to pass empty state
                   modifiers: ModifiersState::empty(),
                 },
@@ -603,7 +767,7 @@ impl<T: 'static> EventLoop<T> {
                     _ => MouseButton::Other(button as u16),
                   },
                   state: ElementState::Released,
-                  device_id: DEVICE_ID,
+                  device_id: device_id(event),
                   // this field is depracted so it is fine to pass empty state
                   modifiers: ModifiersState::empty(),
                 },
@@ -617,18 +781,40 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
-            window.connect_scroll_event(move |_, event| {
-              let (x, y) = event.delta();
+            // Wayland reports a wheel click both as a discrete and a smooth scroll event, with the
+            // same time.
+            let last_discrete_scroll = Cell::new(None);
+            window.connect_scroll_event(move |window, event| {
+              let delta = match event.direction() {
+                ScrollDirection::Smooth => {
+                  if last_discrete_scroll.get() == Some(event.time()) {
+                    return Inhibit(false);
+                  }
+                  smooth_scroll_delta(event, window.scale_factor() as f64)
+                }
+                direction => {
+                  last_discrete_scroll.set(Some(event.time()));
+                  match direction {
+                    ScrollDirection::Up => MouseScrollDelta::LineDelta(0.0, 1.0),
+                    ScrollDirection::Down => MouseScrollDelta::LineDelta(0.0, -1.0),
+                    ScrollDirection::Left => MouseScrollDelta::LineDelta(-1.0, 0.0),
+                    ScrollDirection::Right => MouseScrollDelta::LineDelta(1.0, 0.0),
+                    _ => return Inhibit(false),
+                  }
+                }
+              };
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::MouseWheel {
-                  device_id: DEVICE_ID,
-                  delta: MouseScrollDelta::LineDelta(x as f32, y as f32),
-                  phase: match event.direction() {
-                    ScrollDirection::Smooth => TouchPhase::Moved,
-                    _ => TouchPhase::Ended,
+                  device_id: device_id(event),
+                  delta,
+                  // The end of a touchpad scroll, kinetic scrolling may follow.
+                  phase: if event.is_stop() {
+                    TouchPhase::Ended
+                  } else {
+                    TouchPhase::Moved
                   },
-                  modifiers: ModifiersState::empty(),
+                  modifiers: keyboard::modifiers_from_state(event.state()),
                 },
               }) {
                 log::warn!("Failed to send scroll event to event channel: {}", e);
@@ -636,16 +822,54 @@ impl<T: 'static> EventLoop<T> {
               Inhibit(false)
             });
 
+            // The hardware keycodes of the keys held down, along with the modifier they are, if
+            // any. GTK doesn't tell repeated presses apart.
+            let pressed_keys = Rc::new(RefCell::new(HashMap::new()));
+            let modifiers = Rc::new(Cell::new(ModifiersState::empty()));
+
+            let (pressed_keys_, modifiers_) = (pressed_keys.clone(), modifiers.clone());
             let tx_clone = event_tx.clone();
-            let keyboard_handler = Rc::new(move |event_key: EventKey, element_state| {
-              // if we have a modifier lets send it
-              let mut mods = keyboard::get_modifiers(event_key.clone());
-              if !mods.is_empty() {
-                // if we release the modifier tell the world
-                if ElementState::Released == element_state {
-                  mods = ModifiersState::empty();
+            window.connect_focus_out_event(move |_, _| {
+              // The keys released while the window isn't focused would never be removed.
+              pressed_keys_.borrow_mut().clear();
+              if !modifiers_.replace(ModifiersState::empty()).is_empty() {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::ModifiersChanged(ModifiersState::empty()),
+                }) {
+                  log::warn!(
+                    "Failed to send modifiers changed event to event channel: {}",
+                    e
+                  );
                 }
+              }
+              Inhibit(false)
+            });
 
+            let tx_clone = event_tx.clone();
+            let keyboard_handler = Rc::new(move |event_key: EventKey, element_state| {
+              let keycode = event_key.hardware_keycode();
+              let key_modifier = keyboard::get_modifiers(event_key.clone());
+              let mut pressed_keys = pressed_keys.borrow_mut();
+              let is_repeat = match element_state {
+                ElementState::Pressed => pressed_keys.insert(keycode, key_modifier).is_some(),
+                ElementState::Released => {
+                  pressed_keys.remove(&keycode);
+                  false
+                }
+              };
+
+              // The state of the event holds the modifiers from before the key was pressed or
+              // released, including the ones held before the window was focused. The modifier
+              // released is kept if the key on the other side is still held.
+              let mods = (keyboard::modifiers_from_state(event_key.state()) - key_modifier)
+                | pressed_keys
+                  .values()
+                  .fold(ModifiersState::empty(), |mods, key_modifier| {
+                    mods | *key_modifier
+                  });
+              drop(pressed_keys);
+              if modifiers.replace(mods) != mods {
                 if let Err(e) = tx_clone.send(Event::WindowEvent {
                   window_id: RootWindowId(id),
                   event: WindowEvent::ModifiersChanged(mods),
@@ -654,21 +878,16 @@ impl<T: 'static> EventLoop<T> {
                     "Failed to send modifiers changed event to event channel: {}",
                     e
                   );
-                } else {
-                  // stop here we don't want to send the key event
-                  // as we emit the `ModifiersChanged`
-                  return Continue(true);
                 }
               }
 
-              // todo: implement repeat?
-              let event = keyboard::make_key_event(&event_key, false, None, element_state);
+              let event = keyboard::make_key_event(&event_key, is_repeat, None, element_state);
 
               if let Some(event) = event {
                 if let Err(e) = tx_clone.send(Event::WindowEvent {
                   window_id: RootWindowId(id),
                   event: WindowEvent::KeyboardInput {
-                    device_id: DEVICE_ID,
+                    device_id: device_id(&event_key),
                     event,
                     is_synthetic: false,
                   },
@@ -679,37 +898,53 @@ impl<T: 'static> EventLoop<T> {
               Continue(true)
             });
 
-            let tx_clone = event_tx.clone();
-            // TODO Add actual IME from system
-            let ime = gtk::IMContextSimple::default();
-            ime.set_client_window(window.window().as_ref());
-            ime.focus_in();
-            ime.connect_commit(move |_, s| {
-              if let Err(e) = tx_clone.send(Event::WindowEvent {
-                window_id: RootWindowId(id),
-                event: WindowEvent::ReceivedImeText(s.to_string()),
-              }) {
-                log::warn!(
-                  "Failed to send received IME text event to event channel: {}",
-                  e
-                );
-              }
+            let ime = ime::connect_ime(&window, id, event_tx.clone());
+            imes.borrow_mut().insert(id, ime.clone());
+
+            // The keys typed while a text is being composed go to the input method, and are
+            // reported with the `Ime` events instead. The release of a key is only skipped if its
+            // press was.
+            let composing_keys = Rc::new(RefCell::new(HashSet::new()));
+            let composing_keys_ = composing_keys.clone();
+            window.connect_focus_out_event(move |_, _| {
+              composing_keys_.borrow_mut().clear();
+              Inhibit(false)
             });
 
             let handler = keyboard_handler.clone();
+            let (ime_, composing_keys_) = (ime.clone(), composing_keys.clone());
             window.connect_key_press_event(move |_, event_key| {
-              handler(event_key.to_owned(), ElementState::Pressed);
-              ime.filter_keypress(event_key);
+              if ime_.preedit_string().0.is_empty() {
+                handler(event_key.to_owned(), ElementState::Pressed);
+              } else {
+                composing_keys_
+                  .borrow_mut()
+                  .insert(event_key.hardware_keycode());
+              }
+              ime_.filter_keypress(event_key);
 
               Inhibit(false)
             });
 
             let handler = keyboard_handler.clone();
             window.connect_key_release_event(move |_, event_key| {
-              handler(event_key.to_owned(), ElementState::Released);
+              if !composing_keys
+                .borrow_mut()
+                .remove(&event_key.hardware_keycode())
+              {
+                handler(event_key.to_owned(), ElementState::Released);
+              }
+              ime.filter_keypress(event_key);
+
               Inhibit(false)
             });
 
+            if drag_and_drop {
+              drop_handler::connect_drop_handler(&window, id, event_tx.clone());
+            }
+
+            pen::connect_pen_handler(&window, id, event_tx.clone());
+
             let tx_clone = event_tx.clone();
             window.connect_window_state_event(move |window, event| {
               let state = event.changed_mask();
@@ -742,6 +977,20 @@ impl<T: 'static> EventLoop<T> {
               Inhibit(false)
             });
 
+            let scale_factor_clone = scale_factor_tx.clone();
+            window.connect_scale_factor_notify(move |window| {
+              // GTK keeps the logical size of the window.
+              let scale_factor = window.scale_factor() as f64;
+              let (w, h) = window.size();
+              let new_inner_size = LogicalSize::new(w, h).to_physical(scale_factor);
+              if let Err(e) = scale_factor_clone.send((id, scale_factor, new_inner_size)) {
+                log::warn!(
+                  "Failed to send scale factor changed event to event channel: {}",
+                  e
+                );
+              }
+            });
+
             // Receive draw events of the window.
             let draw_clone = draw_tx.clone();
             window.connect_draw(move |_, cr| {
@@ -875,6 +1124,7 @@ impl<T: 'static> EventLoop<T> {
       user_event_tx,
       events: event_rx,
       draws: draw_rx,
+      scale_factor_changes: scale_factor_rx,
     };
 
     Ok(event_loop)
@@ -935,6 +1185,7 @@ impl<T: 'static> EventLoop<T> {
         let window_target = &self.window_target;
         let events = &self.events;
         let draws = &self.draws;
+        let scale_factor_changes = &self.scale_factor_changes;
 
         window_target.p.app.activate();
 
@@ -948,7 +1199,7 @@ impl<T: 'static> EventLoop<T> {
                 break code;
               }
               ControlFlow::Wait => {
-                if !events.is_empty() || !draws.is_empty() {
+                if !events.is_empty() || !scale_factor_changes.is_empty() || !draws.is_empty() {
                   callback(
                     Event::NewEvents(StartCause::WaitCancelled {
                       start: Instant::now(),
@@ -974,7 +1225,7 @@ impl<T: 'static> EventLoop<T> {
                     &mut control_flow,
                   );
                   state = EventState::EventQueue;
-                } else if !events.is_empty() {
+                } else if !events.is_empty() || !scale_factor_changes.is_empty() {
                   callback(
                     Event::NewEvents(StartCause::WaitCancelled {
                       start,
@@ -1002,20 +1253,48 @@ impl<T: 'static> EventLoop<T> {
                 callback(Event::LoopDestroyed, window_target, &mut control_flow);
                 break (code);
               }
-              _ => match events.try_recv() {
-                Ok(event) => match event {
-                  Event::LoopDestroyed => control_flow = ControlFlow::ExitWithCode(1),
-                  _ => callback(event, window_target, &mut control_flow),
-                },
-                Err(_) => {
-                  callback(Event::MainEventsCleared, window_target, &mut control_flow);
-                  if draws.is_empty() {
-                    state = EventState::NewStart;
-                  } else {
-                    state = EventState::DrawQueue;
+              _ => {
+                // The scale factor changes are handled first, so they come before the `Resized`
+                // and `Moved` events caused by the same change.
+                if let Ok((id, scale_factor, mut new_inner_size)) = scale_factor_changes.try_recv()
+                {
+                  let suggested_size = new_inner_size;
+                  callback(
+                    Event::WindowEvent {
+                      window_id: RootWindowId(id),
+                      event: WindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size: &mut new_inner_size,
+                      },
+                    },
+                    window_target,
+                    &mut control_flow,
+                  );
+                  // GTK already keeps the logical size, only resize if the application asked for
+                  // another size.
+                  if new_inner_size != suggested_size {
+                    if let Some(window) = window_target.p.app.window_by_id(id.0) {
+                      let (w, h) = new_inner_size.to_logical::<i32>(scale_factor).into();
+                      window.resize(w, h);
+                    }
+                  }
+                } else {
+                  match events.try_recv() {
+                    Ok(event) => match event {
+                      Event::LoopDestroyed => control_flow = ControlFlow::ExitWithCode(1),
+                      _ => callback(event, window_target, &mut control_flow),
+                    },
+                    Err(_) => {
+                      callback(Event::MainEventsCleared, window_target, &mut control_flow);
+                      if draws.is_empty() {
+                        state = EventState::NewStart;
+                      } else {
+                        state = EventState::DrawQueue;
+                      }
+                    }
                   }
                 }
-              },
+              }
             },
             EventState::DrawQueue => match control_flow {
               ControlFlow::ExitWithCode(code) => {
@@ -1111,3 +1390,43 @@ fn is_main_thread() -> bool {
 fn is_main_thread() -> bool {
   std::thread::current().name() == Some("main")
 }
+
+/// The delta of a smooth scroll event, in pixels for touchpads on Wayland and in lines otherwise.
+fn smooth_scroll_delta(event: &gdk::EventScroll, scale_factor: f64) -> MouseScrollDelta {
+  // GDK uses positive values to scroll down, and a unit of one wheel click.
+  let (x, y) = event.delta();
+  let is_touchpad = event.source_device().map_or(false, |device| {
+    device.source() == gdk::InputSource::Touchpad
+  });
+  // On X11, the deltas are in the scroll increments of the device, which can't be converted to
+  // pixels.
+  let is_wayland = event
+    .window()
+    .map_or(false, |window| window.display().backend().is_wayland());
+  if is_touchpad && is_wayland {
+    // GDK divides the distance in pixels reported by the compositor by 10.
+    MouseScrollDelta::PixelDelta(
+      LogicalPosition::new(x * 10.0, -y * 10.0).to_physical(scale_factor),
+    )
+  } else {
+    MouseScrollDelta::LineDelta(x as f32, -y as f32)
+  }
+}
+
+/// Reports a device being plugged in or out, unless device events are filtered out entirely.
+fn send_device_event<T>(
+  filter: &Cell<DeviceEventFilter>,
+  tx: &crossbeam_channel::Sender<Event<'static, T>>,
+  device: &gdk::Device,
+  event: DeviceEvent,
+) {
+  if filter.get() == DeviceEventFilter::Always {
+    return;
+  }
+  if let Err(e) = tx.send(Event::DeviceEvent {
+    device_id: RootDeviceId(DeviceId::from_gdk(device)),
+    event,
+  }) {
+    log::warn!("Failed to send device event to event channel: {}", e);
+  }
+}
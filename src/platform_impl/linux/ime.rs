@@ -0,0 +1,76 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use crossbeam_channel::Sender;
+use gtk::{prelude::*, IMMulticontext};
+
+use crate::{
+  event::{Event, Ime, WindowEvent},
+  window::WindowId as RootWindowId,
+};
+
+use super::window::WindowId;
+
+/// Creates the input method context of `window`, which uses the input method of the system
+/// (IBus, Fcitx, ...), and emits the `Ime` and `ReceivedImeText` events.
+///
+/// The key events of the window must be passed to `filter_keypress`.
+pub(crate) fn connect_ime<T: 'static>(
+  window: &gtk::Window,
+  id: WindowId,
+  tx: Sender<Event<'static, T>>,
+) -> IMMulticontext {
+  let ime = IMMulticontext::new();
+  ime.set_client_window(window.window().as_ref());
+  ime.set_use_preedit(true);
+
+  let send = move |event: WindowEvent<'static>| {
+    if let Err(e) = tx.send(Event::WindowEvent {
+      window_id: RootWindowId(id),
+      event,
+    }) {
+      log::warn!("Failed to send IME event to event channel: {}", e);
+    }
+  };
+
+  let send_ = send.clone();
+  ime.connect_preedit_changed(move |ime| {
+    let (text, _, cursor) = ime.preedit_string();
+    // GTK gives the cursor position in characters.
+    let cursor = text
+      .char_indices()
+      .nth(cursor as usize)
+      .map_or(text.len(), |(i, _)| i);
+    send_(WindowEvent::Ime(Ime::Preedit(
+      text.to_string(),
+      Some((cursor, cursor)),
+    )));
+  });
+
+  let send_ = send.clone();
+  ime.connect_preedit_end(move |_| {
+    send_(WindowEvent::Ime(Ime::Preedit(String::new(), None)));
+  });
+
+  let send_ = send.clone();
+  ime.connect_commit(move |_, text| {
+    send_(WindowEvent::Ime(Ime::Commit(text.to_string())));
+    send_(WindowEvent::ReceivedImeText(text.to_string()));
+  });
+
+  let (ime_, send_) = (ime.clone(), send.clone());
+  window.connect_focus_in_event(move |_, _| {
+    ime_.focus_in();
+    send_(WindowEvent::Ime(Ime::Enabled));
+    Inhibit(false)
+  });
+
+  let ime_ = ime.clone();
+  window.connect_focus_out_event(move |_, _| {
+    ime_.focus_out();
+    send(WindowEvent::Ime(Ime::Disabled));
+    Inhibit(false)
+  });
+
+  ime
+}
@@ -158,6 +158,29 @@ pub(crate) fn get_modifiers(key: EventKey) -> ModifiersState {
   result
 }
 
+/// The modifiers held according to the state of an event.
+pub(crate) fn modifiers_from_state(state: gdk::ModifierType) -> ModifiersState {
+  let mut result = ModifiersState::empty();
+  result.set(
+    ModifiersState::SHIFT,
+    state.contains(gdk::ModifierType::SHIFT_MASK),
+  );
+  result.set(
+    ModifiersState::CONTROL,
+    state.contains(gdk::ModifierType::CONTROL_MASK),
+  );
+  result.set(
+    ModifiersState::ALT,
+    state.contains(gdk::ModifierType::MOD1_MASK),
+  );
+  // X11 reports the Super key as `MOD4`.
+  result.set(
+    ModifiersState::SUPER,
+    state.intersects(gdk::ModifierType::SUPER_MASK | gdk::ModifierType::MOD4_MASK),
+  );
+  result
+}
+
 pub(crate) fn make_key_event(
   key: &EventKey,
   is_repeat: bool,
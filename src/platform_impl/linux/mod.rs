@@ -10,22 +10,28 @@
 ))]
 
 mod clipboard;
+mod drag_source;
+mod drop_handler;
 mod event_loop;
 mod global_shortcut;
 mod icon;
+mod ime;
 mod keyboard;
 mod keycode;
 mod menu;
 mod monitor;
+mod pen;
+mod raw_input;
 #[cfg(feature = "tray")]
 mod system_tray;
+mod theme;
 mod window;
 pub mod x11;
 
 #[cfg(feature = "tray")]
 pub use self::system_tray::{SystemTray, SystemTrayBuilder};
 pub use self::{
-  clipboard::Clipboard,
+  clipboard::{Clipboard, ClipboardKind},
   global_shortcut::{GlobalShortcut, ShortcutManager},
   keycode::{keycode_from_scancode, keycode_to_scancode},
   menu::{Menu, MenuItemAttributes},
@@ -35,6 +41,8 @@ pub use icon::PlatformIcon;
 pub use monitor::{MonitorHandle, VideoMode};
 pub use window::{hit_test, Window, WindowId};
 
+use gtk::prelude::*;
+
 use crate::{event::DeviceId as RootDeviceId, keyboard::Key};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -61,6 +69,7 @@ pub struct PlatformSpecificWindowBuilderAttributes {
   pub parent: Parent,
   pub skip_taskbar: bool,
   pub auto_transparent: bool,
+  pub drag_and_drop: bool,
 }
 
 impl Default for PlatformSpecificWindowBuilderAttributes {
@@ -69,6 +78,7 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
       parent: Default::default(),
       skip_taskbar: Default::default(),
       auto_transparent: true,
+      drag_and_drop: true,
     }
   }
 }
@@ -77,11 +87,15 @@ unsafe impl Send for PlatformSpecificWindowBuilderAttributes {}
 unsafe impl Sync for PlatformSpecificWindowBuilderAttributes {}
 
 #[derive(Debug, Clone)]
-pub struct OsError;
+pub enum OsError {
+  GrabFailed(gdk::GrabStatus),
+}
 
 impl std::fmt::Display for OsError {
-  fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-    Ok(())
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+    match self {
+      OsError::GrabFailed(status) => write!(f, "Failed to grab the cursor: {:?}", status),
+    }
   }
 }
 
@@ -92,7 +106,43 @@ impl DeviceId {
   pub unsafe fn dummy() -> Self {
     Self(0)
   }
+
+  /// The id of `device`. On X11, it is the XInput2 device id, the same one the raw events report
+  /// as their `sourceid`.
+  pub(crate) fn from_gdk(device: &gdk::Device) -> Self {
+    if device.display().backend().is_x11() {
+      let id = unsafe { gdk_x11_sys::gdk_x11_device_get_id(device.as_ptr() as *mut _) };
+      Self(id as usize)
+    } else {
+      // Wayland doesn't expose an id, the `GdkDevice` lives as long as the device though.
+      Self(device.as_ptr() as usize)
+    }
+  }
+
+  pub fn persistent_identifier(&self) -> Option<String> {
+    // GDK can only be used from the main thread.
+    if !gtk::is_initialized_main_thread() {
+      return None;
+    }
+    let seat = gdk::Display::default()?.default_seat()?;
+    let device = seat
+      .slaves(gdk::SeatCapabilities::ALL)
+      .into_iter()
+      .find(|device| Self::from_gdk(device) == *self)?;
+    Some(format!(
+      "{}:{}:{}",
+      device.vendor_id()?,
+      device.product_id()?,
+      device.name()?
+    ))
+  }
 }
 
-// FIXME: currently we use a dummy device id, find if we can get device id from gtk
-pub(crate) const DEVICE_ID: RootDeviceId = RootDeviceId(DeviceId(0));
+/// The id of the physical device which produced `event`.
+pub(crate) fn device_id(event: &gdk::Event) -> RootDeviceId {
+  event
+    .source_device()
+    .or_else(|| event.device())
+    .map(|device| RootDeviceId(DeviceId::from_gdk(&device)))
+    .unwrap_or(RootDeviceId(DeviceId(0)))
+}
@@ -0,0 +1,111 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cell::Cell, rc::Rc};
+
+use crossbeam_channel::Sender;
+use gdk::{AxisUse, DeviceToolType, InputSource};
+use gtk::{prelude::*, Inhibit};
+
+use crate::{
+  dpi::LogicalPosition,
+  event::{Event, Pen, PenPhase, PenTool, WindowEvent},
+  window::WindowId as RootWindowId,
+};
+
+use super::{device_id, window::WindowId};
+
+/// Emits the `Pen` events of `window`, from the events of the tablet devices.
+pub(crate) fn connect_pen_handler<T: 'static>(
+  window: &gtk::Window,
+  id: WindowId,
+  tx: Sender<Event<'static, T>>,
+) {
+  // The proximity events don't hold a position, the last one is used instead.
+  let location = Cell::new((0.0, 0.0));
+  let send = Rc::new(
+    move |window: &gtk::Window, event: &gdk::Event, phase, position: Option<(f64, f64)>| {
+      let tool = match pen_tool(event) {
+        Some(tool) => tool,
+        None => return,
+      };
+      if let Some(position) = position {
+        location.set(position);
+      }
+      let (x, y) = location.get();
+      let tilt = match (event.axis(AxisUse::Xtilt), event.axis(AxisUse::Ytilt)) {
+        (Some(x), Some(y)) => Some((x, y)),
+        _ => None,
+      };
+      if let Err(e) = tx.send(Event::WindowEvent {
+        window_id: RootWindowId(id),
+        event: WindowEvent::Pen(Pen {
+          device_id: device_id(event),
+          phase,
+          tool,
+          location: LogicalPosition::new(x, y).to_physical(window.scale_factor() as f64),
+          // GDK normalizes the pressure between 0 and 1, and the tilt between -1 and 1.
+          pressure: event.axis(AxisUse::Pressure),
+          tilt,
+        }),
+      }) {
+        log::warn!("Failed to send pen event to event channel: {}", e);
+      }
+    },
+  );
+
+  let send_ = send.clone();
+  window.connect_proximity_in_event(move |window, event| {
+    // Use the position of the pen if GDK can tell it.
+    let position = event
+      .device()
+      .and_then(|device| match device.window_at_position() {
+        (Some(_), x, y) => Some((x as f64, y as f64)),
+        _ => None,
+      });
+    send_(window, event, PenPhase::Entered, position);
+    Inhibit(false)
+  });
+
+  let send_ = send.clone();
+  window.connect_proximity_out_event(move |window, event| {
+    send_(window, event, PenPhase::Left, None);
+    Inhibit(false)
+  });
+
+  let send_ = send.clone();
+  window.connect_motion_notify_event(move |window, event| {
+    send_(window, event, PenPhase::Moved, Some(event.position()));
+    Inhibit(false)
+  });
+
+  let send_ = send.clone();
+  window.connect_button_press_event(move |window, event| {
+    // The tip of the pen is reported as the first button, the others are the stylus buttons.
+    if event.button() == 1 {
+      send_(window, event, PenPhase::Down, Some(event.position()));
+    }
+    Inhibit(false)
+  });
+
+  window.connect_button_release_event(move |window, event| {
+    if event.button() == 1 {
+      send(window, event, PenPhase::Up, Some(event.position()));
+    }
+    Inhibit(false)
+  });
+}
+
+/// The tool which produced `event`, if it comes from a tablet.
+fn pen_tool(event: &gdk::Event) -> Option<PenTool> {
+  let source = event.source_device()?.source();
+  if source != InputSource::Pen && source != InputSource::Eraser {
+    return None;
+  }
+  let tool_type = event.device_tool().map(|tool| tool.tool_type());
+  if tool_type == Some(DeviceToolType::Eraser) || source == InputSource::Eraser {
+    Some(PenTool::Eraser)
+  } else {
+    Some(PenTool::Pen)
+  }
+}
@@ -0,0 +1,214 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cell::Cell, os::raw::c_int, rc::Rc, slice};
+
+use crossbeam_channel::Sender;
+use glib::{Continue, IOCondition};
+use gtk::prelude::*;
+
+use crate::{
+  event::{
+    DeviceEvent, DeviceId as RootDeviceId, ElementState, Event, MouseScrollDelta, RawKeyEvent,
+  },
+  event_loop::DeviceEventFilter,
+  keyboard::KeyCode,
+};
+
+use super::{
+  x11::{
+    ffi,
+    xdisplay::{x_error_callback, XConnection},
+  },
+  DeviceId,
+};
+
+/// A dedicated connection to the X server, used to receive the XInput2 raw events which GDK
+/// doesn't report.
+struct RawInput {
+  xconn: XConnection,
+  /// The major opcode of the XInput extension.
+  opcode: c_int,
+}
+
+impl RawInput {
+  fn new() -> Option<Self> {
+    let xconn = XConnection::new(Some(x_error_callback)).ok()?;
+    let mut raw_input = Self { xconn, opcode: 0 };
+    unsafe { raw_input.init() }.then(|| raw_input)
+  }
+
+  /// Checks that XInput 2.0 is available and selects the raw events of all the devices.
+  unsafe fn init(&mut self) -> bool {
+    let xconn = &self.xconn;
+    let (mut event, mut error) = (0, 0);
+    if (xconn.xlib.XQueryExtension)(
+      xconn.display,
+      b"XInputExtension\0".as_ptr() as *const _,
+      &mut self.opcode,
+      &mut event,
+      &mut error,
+    ) == 0
+    {
+      return false;
+    }
+    let (mut major, mut minor) = (2, 0);
+    if (xconn.xinput2.XIQueryVersion)(xconn.display, &mut major, &mut minor)
+      != ffi::Success as c_int
+    {
+      return false;
+    }
+
+    let mut mask = [0u8; 4];
+    for event in [
+      ffi::XI_RawKeyPress,
+      ffi::XI_RawKeyRelease,
+      ffi::XI_RawButtonPress,
+      ffi::XI_RawButtonRelease,
+      ffi::XI_RawMotion,
+    ] {
+      ffi::XISetMask(&mut mask, event);
+    }
+    let mut event_mask = ffi::XIEventMask {
+      deviceid: ffi::XIAllMasterDevices,
+      mask_len: mask.len() as c_int,
+      mask: mask.as_mut_ptr(),
+    };
+    let root = (xconn.xlib.XDefaultRootWindow)(xconn.display);
+    (xconn.xinput2.XISelectEvents)(xconn.display, root, &mut event_mask, 1);
+    (xconn.xlib.XFlush)(xconn.display);
+    true
+  }
+
+  /// Reads all the pending events and reports the ones that translate to a `DeviceEvent`, along
+  /// with the device which produced them.
+  fn process_events(&self, mut callback: impl FnMut(RootDeviceId, DeviceEvent)) {
+    let xconn = &self.xconn;
+    unsafe {
+      while (xconn.xlib.XPending)(xconn.display) > 0 {
+        let mut xevent: ffi::XEvent = std::mem::zeroed();
+        (xconn.xlib.XNextEvent)(xconn.display, &mut xevent);
+
+        let mut cookie = xevent.generic_event_cookie;
+        if cookie.type_ != ffi::GenericEvent
+          || cookie.extension != self.opcode
+          || (xconn.xlib.XGetEventData)(xconn.display, &mut cookie) == 0
+        {
+          continue;
+        }
+
+        let raw = &*(cookie.data as *const ffi::XIRawEvent);
+        // `deviceid` is the master device, `sourceid` the physical one.
+        let device_id = RootDeviceId(DeviceId(raw.sourceid as usize));
+        let mut callback = |event| callback(device_id, event);
+        let state = match cookie.evtype {
+          ffi::XI_RawKeyPress | ffi::XI_RawButtonPress => ElementState::Pressed,
+          _ => ElementState::Released,
+        };
+        match cookie.evtype {
+          ffi::XI_RawMotion => process_motion(raw, &mut callback),
+          ffi::XI_RawKeyPress | ffi::XI_RawKeyRelease => {
+            callback(DeviceEvent::Key(RawKeyEvent {
+              physical_key: KeyCode::from_scancode(raw.detail as u32),
+              state,
+            }));
+          }
+          ffi::XI_RawButtonPress | ffi::XI_RawButtonRelease => match wheel_delta(raw.detail) {
+            Some(delta) => {
+              if state == ElementState::Pressed {
+                callback(DeviceEvent::MouseWheel { delta });
+              }
+            }
+            None => callback(DeviceEvent::Button {
+              button: raw.detail as u32,
+              state,
+            }),
+          },
+          _ => (),
+        }
+
+        (xconn.xlib.XFreeEventData)(xconn.display, &mut cookie);
+      }
+    }
+  }
+}
+
+/// Reports the unaccelerated values of every axis of the event, and the motion of the first two.
+unsafe fn process_motion(raw: &ffi::XIRawEvent, callback: &mut impl FnMut(DeviceEvent)) {
+  let mask = slice::from_raw_parts(raw.valuators.mask, raw.valuators.mask_len as usize);
+  // The values are only given for the axes set in the mask.
+  let mut values = raw.raw_values;
+  let mut delta = (0.0, 0.0);
+  for axis in 0..(mask.len() * 8) as i32 {
+    if !ffi::XIMaskIsSet(mask, axis) {
+      continue;
+    }
+    let value = *values;
+    values = values.offset(1);
+
+    match axis {
+      0 => delta.0 = value,
+      1 => delta.1 = value,
+      _ => (),
+    }
+    callback(DeviceEvent::Motion {
+      axis: axis as u32,
+      value,
+    });
+  }
+
+  if delta != (0.0, 0.0) {
+    callback(DeviceEvent::MouseMotion { delta });
+  }
+}
+
+/// The scroll delta of the buttons X uses for the mouse wheel.
+fn wheel_delta(button: c_int) -> Option<MouseScrollDelta> {
+  let (x, y) = match button {
+    4 => (0.0, 1.0),
+    5 => (0.0, -1.0),
+    6 => (-1.0, 0.0),
+    7 => (1.0, 0.0),
+    _ => return None,
+  };
+  Some(MouseScrollDelta::LineDelta(x, y))
+}
+
+/// Starts emitting the `DeviceEvent`s read from the XInput2 raw events, unless `filter` says
+/// otherwise.
+///
+/// Does nothing if XInput 2.0 isn't available.
+pub(crate) fn connect_raw_input<T: 'static>(
+  app: &gtk::Application,
+  filter: Rc<Cell<DeviceEventFilter>>,
+  tx: Sender<Event<'static, T>>,
+) {
+  let raw_input = match RawInput::new() {
+    Some(raw_input) => raw_input,
+    None => {
+      log::warn!("XInput 2.0 is not available, device events won't be emitted");
+      return;
+    }
+  };
+
+  let app = app.clone();
+  let fd = raw_input.xconn.x11_fd;
+  glib::source::unix_fd_add_local(fd, IOCondition::IN, move |_, _| {
+    let emit = match filter.get() {
+      DeviceEventFilter::Always => false,
+      DeviceEventFilter::Unfocused => app.windows().iter().any(|window| window.is_active()),
+      DeviceEventFilter::Never => true,
+    };
+    // The events are read even when filtered out, so they don't pile up.
+    raw_input.process_events(|device_id, event| {
+      if !emit {
+        return;
+      }
+
+      if let Err(e) = tx.send(Event::DeviceEvent { device_id, event }) {
+        log::warn!("Failed to send device event to event channel: {}", e);
+      }
+    });
+    Continue(true)
+  });
+}
@@ -0,0 +1,170 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+  cell::{Cell, RefCell},
+  rc::Rc,
+};
+
+use gio::{prelude::*, BusType, DBusCallFlags, DBusProxy, DBusProxyFlags};
+use glib::Variant;
+use gtk::{traits::SettingsExt, Settings};
+
+use crate::window::Theme;
+
+// Currently GTK doesn't provide feature for detect theme, so we need to check theme manually.
+// ref: https://github.com/WebKit/WebKit/blob/e44ffaa0d999a9807f76f1805943eea204cfdfbc/Source/WebKit/UIProcess/API/gtk/PageClientImpl.cpp#L587
+const GTK_THEME_SUFFIX_LIST: [&str; 3] = ["-dark", "-Dark", "-Darker"];
+
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+thread_local! {
+  /// Whether the `color-scheme` of the freedesktop appearance settings prefers a dark theme.
+  static PREFERS_DARK: Cell<bool> = Cell::new(false);
+  /// The theme set by the application, `None` to follow the system.
+  static PREFERRED_THEME: Cell<Option<Theme>> = Cell::new(None);
+  /// Emits `ThemeChanged` if the theme changed since the last call.
+  static CHECK_THEME: RefCell<Option<Rc<dyn Fn()>>> = RefCell::new(None);
+}
+
+/// The theme of the application, which is the theme of the system unless one was set with
+/// [`set_preferred_theme`].
+pub(crate) fn theme() -> Theme {
+  PREFERRED_THEME.with(Cell::get).unwrap_or_else(system_theme)
+}
+
+/// Makes the application use `theme`, or follow the theme of the system if `None`.
+///
+/// The GTK settings are only overridden for this process, and resetting them brings back the
+/// values of the system, so the original theme name isn't lost.
+pub(crate) fn set_preferred_theme(theme: Option<Theme>) {
+  PREFERRED_THEME.with(|preferred| preferred.set(theme));
+
+  if let Some(settings) = Settings::default() {
+    settings.reset_property("gtk-theme-name");
+    settings.reset_property("gtk-application-prefer-dark-theme");
+    match theme {
+      Some(Theme::Dark) => settings.set_gtk_application_prefer_dark_theme(true),
+      Some(Theme::Light) => {
+        if let Some(name) = settings.gtk_theme_name() {
+          // Remove dark variant.
+          if let Some(name) = GTK_THEME_SUFFIX_LIST
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))
+          {
+            settings.set_gtk_theme_name(Some(name));
+          }
+        }
+        settings.set_gtk_application_prefer_dark_theme(false);
+      }
+      None => (),
+    }
+  }
+
+  // The settings may not have changed, e.g. when following a theme only given by the portal.
+  let check = CHECK_THEME.with(|check| check.borrow().clone());
+  if let Some(check) = check {
+    check();
+  }
+}
+
+/// The theme of the system, from the GTK theme and the freedesktop `color-scheme` setting.
+fn system_theme() -> Theme {
+  if let Some(settings) = Settings::default() {
+    if settings.is_gtk_application_prefer_dark_theme() {
+      return Theme::Dark;
+    }
+    if let Some(theme) = settings.gtk_theme_name() {
+      if GTK_THEME_SUFFIX_LIST.iter().any(|t| theme.ends_with(t)) {
+        return Theme::Dark;
+      }
+    }
+  }
+  if PREFERS_DARK.with(Cell::get) {
+    Theme::Dark
+  } else {
+    Theme::Light
+  }
+}
+
+/// Calls `callback` with the new theme whenever the theme of the application changes, either
+/// because the theme of the system changed or because one was set with [`set_preferred_theme`].
+pub(crate) fn connect_theme_changed(callback: impl Fn(Theme) + 'static) {
+  let current = Cell::new(theme());
+  let check = Rc::new(move || {
+    let new_theme = theme();
+    if current.replace(new_theme) != new_theme {
+      callback(new_theme);
+    }
+  });
+  CHECK_THEME.with(|check_| *check_.borrow_mut() = Some(check.clone()));
+
+  if let Some(settings) = Settings::default() {
+    let check_ = check.clone();
+    settings.connect_gtk_theme_name_notify(move |_| check_());
+    let check_ = check.clone();
+    settings.connect_gtk_application_prefer_dark_theme_notify(move |_| check_());
+  }
+
+  // The settings portal is read asynchronously, so it doesn't hold up the startup if it is slow
+  // or missing.
+  DBusProxy::for_bus(
+    BusType::Session,
+    DBusProxyFlags::NONE,
+    None,
+    "org.freedesktop.portal.Desktop",
+    "/org/freedesktop/portal/desktop",
+    "org.freedesktop.portal.Settings",
+    None::<&gio::Cancellable>,
+    move |proxy| {
+      let proxy = match proxy {
+        Ok(proxy) => proxy,
+        Err(e) => {
+          log::warn!("Failed to connect to the settings portal: {}", e);
+          return;
+        }
+      };
+
+      let check_ = check.clone();
+      proxy.call(
+        "Read",
+        Some(&(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY).to_variant()),
+        DBusCallFlags::NONE,
+        -1,
+        None::<&gio::Cancellable>,
+        move |reply| {
+          // Fails on desktops which don't provide the setting.
+          if let Some((value,)) = reply.ok().and_then(|reply| reply.get::<(Variant,)>()) {
+            set_color_scheme(&value);
+            check_();
+          }
+        },
+      );
+
+      proxy.connect_local("g-signal", false, move |args| {
+        let signal = args[2].get::<String>().ok()?;
+        let parameters = args[3].get::<Variant>().ok()?;
+        if signal == "SettingChanged" {
+          if let Some((namespace, key, value)) = parameters.get::<(String, String, Variant)>() {
+            if namespace == APPEARANCE_NAMESPACE && key == COLOR_SCHEME_KEY {
+              set_color_scheme(&value);
+              check();
+            }
+          }
+        }
+        None
+      });
+    },
+  );
+}
+
+/// Stores the value of the `color-scheme` setting, where 1 means that a dark theme is preferred.
+fn set_color_scheme(value: &Variant) {
+  // Older versions of the portal wrap the value in another variant.
+  let mut value = value.clone();
+  while let Some(inner) = value.as_variant() {
+    value = inner;
+  }
+  PREFERS_DARK.with(|prefers_dark| prefers_dark.set(value.get::<u32>() == Some(1)));
+}
@@ -6,11 +6,12 @@ use std::{
   collections::VecDeque,
   rc::Rc,
   sync::atomic::{AtomicBool, AtomicI32, Ordering},
+  time::Duration,
 };
 
-use gdk::{WindowEdge, WindowState};
-use glib::translate::ToGlibPtr;
-use gtk::{prelude::*, traits::SettingsExt, AccelGroup, Orientation, Settings};
+use gdk::{GrabStatus, SeatCapabilities, WindowEdge, WindowState};
+use glib::{translate::ToGlibPtr, Continue};
+use gtk::{prelude::*, AccelGroup, Orientation};
 use raw_window_handle::{RawDisplayHandle, RawWindowHandle, XlibDisplayHandle, XlibWindowHandle};
 
 use crate::{
@@ -20,13 +21,14 @@ use crate::{
   menu::{MenuId, MenuItem},
   monitor::MonitorHandle as RootMonitorHandle,
   window::{
-    CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes, BORDERLESS_RESIZE_INSET,
+    CursorIcon, DragData, DragOptions, Fullscreen, Theme, UserAttentionType, WindowAttributes,
+    BORDERLESS_RESIZE_INSET,
   },
 };
 
 use super::{
-  event_loop::EventLoopWindowTarget, menu, monitor::MonitorHandle, Parent,
-  PlatformSpecificWindowBuilderAttributes,
+  event_loop::EventLoopWindowTarget, menu, monitor::MonitorHandle, theme, x11::ffi, OsError,
+  Parent, PlatformSpecificWindowBuilderAttributes,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -38,10 +40,6 @@ impl WindowId {
   }
 }
 
-// Currently GTK doesn't provide feature for detect theme, so we need to check theme manually.
-// ref: https://github.com/WebKit/WebKit/blob/e44ffaa0d999a9807f76f1805943eea204cfdfbc/Source/WebKit/UIProcess/API/gtk/PageClientImpl.cpp#L587
-const GTK_THEME_SUFFIX_LIST: [&'static str; 3] = ["-dark", "-Dark", "-Darker"];
-
 pub struct Window {
   /// Window id.
   pub(crate) window_id: WindowId,
@@ -59,6 +57,7 @@ pub struct Window {
   maximized: Rc<AtomicBool>,
   minimized: Rc<AtomicBool>,
   fullscreen: RefCell<Option<Fullscreen>>,
+  cursor_grabbed: Rc<AtomicBool>,
 }
 
 impl Window {
@@ -220,27 +219,8 @@ impl Window {
       window.set_icon(Some(&icon.inner.into()));
     }
 
-    let settings = Settings::default();
-
-    if let Some(settings) = settings {
-      if let Some(preferred_theme) = attributes.preferred_theme {
-        match preferred_theme {
-          Theme::Dark => settings.set_gtk_application_prefer_dark_theme(true),
-          Theme::Light => {
-            let theme_name = settings.gtk_theme_name().map(|t| t.as_str().to_owned());
-            if let Some(theme) = theme_name {
-              // Remove dark variant.
-              if let Some(theme) = GTK_THEME_SUFFIX_LIST
-                .iter()
-                .find(|t| theme.ends_with(*t))
-                .map(|v| theme.strip_suffix(v))
-              {
-                settings.set_gtk_theme_name(theme);
-              }
-            }
-          }
-        }
-      }
+    if let Some(preferred_theme) = attributes.preferred_theme {
+      theme::set_preferred_theme(Some(preferred_theme));
     }
 
     if attributes.visible {
@@ -292,14 +272,64 @@ impl Window {
       scale_factor_clone.store(window.scale_factor(), Ordering::Release);
     });
 
+    // The cursor grab is released while the window is unfocused and taken again when the focus
+    // comes back.
+    let cursor_grabbed = Rc::new(AtomicBool::new(false));
+    let grabbed_clone = cursor_grabbed.clone();
+    window.connect_focus_in_event(move |window, _| {
+      if grabbed_clone.load(Ordering::Acquire) {
+        if let Err(e) = grab_cursor(window) {
+          log::warn!("Failed to grab the cursor again: {}", e);
+        }
+      }
+      Inhibit(false)
+    });
+    let grabbed_clone = cursor_grabbed.clone();
+    window.connect_focus_out_event(move |window, _| {
+      if grabbed_clone.load(Ordering::Acquire) {
+        ungrab_cursor(window);
+      }
+      Inhibit(false)
+    });
+    // Another grab of the application, e.g. of a menu, replaced the cursor grab, which is taken
+    // again once it is released.
+    let grabbed_clone = cursor_grabbed.clone();
+    window.connect_grab_broken_event(move |window, _| {
+      let (window, grabbed) = (window.clone(), grabbed_clone.clone());
+      glib::timeout_add_local(Duration::from_millis(100), move || {
+        if !grabbed.load(Ordering::Acquire) || !window.is_active() {
+          // The grab is taken again when the focus comes back.
+          return Continue(false);
+        }
+        let pointer = window
+          .display()
+          .default_seat()
+          .and_then(|seat| seat.pointer());
+        if pointer.map_or(false, |pointer| {
+          window.display().device_is_grabbed(&pointer)
+        }) {
+          return Continue(true);
+        }
+        if let Err(e) = grab_cursor(&window) {
+          log::warn!("Failed to grab the cursor again: {}", e);
+        }
+        Continue(false)
+      });
+      Inhibit(false)
+    });
+
     // Check if we should paint the transparent background ourselves.
     let mut transparent = false;
     if attributes.transparent && pl_attribs.auto_transparent {
       transparent = true;
     }
-    if let Err(e) =
-      window_requests_tx.send((window_id, WindowRequest::WireUpEvents { transparent }))
-    {
+    if let Err(e) = window_requests_tx.send((
+      window_id,
+      WindowRequest::WireUpEvents {
+        transparent,
+        drag_and_drop: pl_attribs.drag_and_drop,
+      },
+    )) {
       log::warn!("Fail to send wire up events request: {}", e);
     }
 
@@ -319,6 +349,7 @@ impl Window {
       maximized,
       minimized,
       fullscreen: RefCell::new(attributes.fullscreen),
+      cursor_grabbed,
     };
 
     win.set_skip_taskbar(pl_attribs.skip_taskbar);
@@ -521,6 +552,16 @@ impl Window {
     Ok(())
   }
 
+  pub fn start_drag(&self, data: DragData, options: DragOptions) -> Result<(), ExternalError> {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::StartDrag(data, options)))
+    {
+      log::warn!("Fail to send start drag request: {}", e);
+    }
+    Ok(())
+  }
+
   pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
     self.fullscreen.replace(fullscreen.clone());
     if let Err(e) = self
@@ -562,8 +603,18 @@ impl Window {
     }
   }
 
-  pub fn set_ime_position<P: Into<Position>>(&self, _position: P) {
-    //TODO
+  pub fn set_ime_position<P: Into<Position>>(&self, position: P) {
+    let (x, y): (i32, i32) = position
+      .into()
+      .to_logical::<i32>(self.scale_factor())
+      .into();
+
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::ImePosition((x, y))))
+    {
+      log::warn!("Fail to send IME position request: {}", e);
+    }
   }
 
   pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
@@ -613,7 +664,13 @@ impl Window {
     Ok(())
   }
 
-  pub fn set_cursor_grab(&self, _grab: bool) -> Result<(), ExternalError> {
+  pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    if grab {
+      grab_cursor(&self.window)?;
+    } else if self.cursor_grabbed.load(Ordering::Acquire) {
+      ungrab_cursor(&self.window);
+    }
+    self.cursor_grabbed.store(grab, Ordering::Release);
     Ok(())
   }
 
@@ -716,16 +773,12 @@ impl Window {
     }
   }
 
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    theme::set_preferred_theme(theme);
+  }
+
   pub fn theme(&self) -> Theme {
-    if let Some(settings) = Settings::default() {
-      let theme_name = settings.gtk_theme_name().map(|s| s.as_str().to_owned());
-      if let Some(theme) = theme_name {
-        if GTK_THEME_SUFFIX_LIST.iter().any(|t| theme.ends_with(t)) {
-          return Theme::Dark;
-        }
-      }
-    }
-    return Theme::Light;
+    theme::theme()
   }
 }
 
@@ -747,6 +800,7 @@ pub enum WindowRequest {
   Minimized(bool),
   Maximized(bool),
   DragWindow,
+  StartDrag(DragData, DragOptions),
   Fullscreen(Option<Fullscreen>),
   Decorations(bool),
   AlwaysOnTop(bool),
@@ -756,13 +810,100 @@ pub enum WindowRequest {
   CursorIcon(Option<CursorIcon>),
   CursorPosition((i32, i32)),
   CursorIgnoreEvents(bool),
-  WireUpEvents { transparent: bool },
+  ImePosition((i32, i32)),
+  WireUpEvents {
+    transparent: bool,
+    drag_and_drop: bool,
+  },
   Redraw,
   Menu((Option<MenuItem>, Option<MenuId>)),
   SetMenu((Option<menu::Menu>, AccelGroup, gtk::MenuBar)),
   GlobalHotKey(u16),
 }
 
+/// Grabs the pointer with the seat of `window`, and confines it to the window on X11.
+fn grab_cursor(window: &impl IsA<gtk::Widget>) -> Result<(), ExternalError> {
+  let gdk_window = window
+    .window()
+    .ok_or_else(|| ExternalError::Os(os_error!(OsError::GrabFailed(GrabStatus::NotViewable))))?;
+  let display = gdk_window.display();
+  // GTK doesn't expose the pointer constraints protocol, which is the only way to confine the
+  // pointer on Wayland.
+  if !display.backend().is_x11() {
+    return Err(ExternalError::NotSupported(NotSupportedError::new()));
+  }
+  let xlib =
+    ffi::Xlib::open().map_err(|_| ExternalError::NotSupported(NotSupportedError::new()))?;
+  let seat = display
+    .default_seat()
+    .ok_or_else(|| ExternalError::NotSupported(NotSupportedError::new()))?;
+
+  // The seat grab lets GDK know about the grab, and emit `grab-broken-event` when another one,
+  // e.g. of a menu, replaces it.
+  match seat.grab(
+    &gdk_window,
+    SeatCapabilities::POINTER,
+    true,
+    None,
+    None,
+    None,
+  ) {
+    GrabStatus::Success => (),
+    status => return Err(ExternalError::Os(os_error!(OsError::GrabFailed(status)))),
+  }
+
+  // The XInput2 grab of GDK can't confine the pointer, so it is replaced by a core grab on the
+  // same connection, which keeps delivering the events to the window. With owner events, they
+  // are reported as usual inside the window, which the pointer can't leave.
+  let status = unsafe {
+    let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _);
+    let xwindow = gdk_x11_sys::gdk_x11_window_get_xid(gdk_window.as_ptr() as *mut _);
+    let status = (xlib.XGrabPointer)(
+      xdisplay as *mut _,
+      xwindow,
+      ffi::True,
+      (ffi::ButtonPressMask
+        | ffi::ButtonReleaseMask
+        | ffi::EnterWindowMask
+        | ffi::LeaveWindowMask
+        | ffi::PointerMotionMask) as _,
+      ffi::GrabModeAsync,
+      ffi::GrabModeAsync,
+      xwindow,
+      0,
+      ffi::CurrentTime,
+    );
+    (xlib.XFlush)(xdisplay as *mut _);
+    status
+  };
+  let status = match status {
+    ffi::GrabSuccess => return Ok(()),
+    ffi::AlreadyGrabbed => GrabStatus::AlreadyGrabbed,
+    ffi::GrabInvalidTime => GrabStatus::InvalidTime,
+    ffi::GrabNotViewable => GrabStatus::NotViewable,
+    _ => GrabStatus::Frozen,
+  };
+  seat.ungrab();
+  Err(ExternalError::Os(os_error!(OsError::GrabFailed(status))))
+}
+
+fn ungrab_cursor(window: &impl IsA<gtk::Widget>) {
+  let display = window.as_ref().display();
+  if !display.backend().is_x11() {
+    return;
+  }
+  if let Ok(xlib) = ffi::Xlib::open() {
+    unsafe {
+      let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _);
+      (xlib.XUngrabPointer)(xdisplay as *mut _, ffi::CurrentTime);
+      (xlib.XFlush)(xdisplay as *mut _);
+    }
+  }
+  if let Some(seat) = display.default_seat() {
+    seat.ungrab();
+  }
+}
+
 pub fn hit_test(window: &gdk::Window, cx: f64, cy: f64) -> WindowEdge {
   let (left, top) = window.position();
   let (w, h) = (window.width(), window.height());
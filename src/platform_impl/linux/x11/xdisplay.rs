@@ -123,6 +123,29 @@ impl fmt::Display for XError {
   }
 }
 
+/// Logs the X errors instead of exiting the process like the default handler of Xlib.
+///
+/// # Safety
+///
+/// Must only be called by Xlib, with a valid `XErrorEvent`.
+pub unsafe extern "C" fn x_error_callback(
+  _display: *mut ffi::Display,
+  event: *mut ffi::XErrorEvent,
+) -> c_int {
+  let error = XError {
+    // TODO get the error text as description
+    description: String::new(),
+    error_code: (*event).error_code,
+    request_code: (*event).request_code,
+    minor_code: (*event).minor_code,
+  };
+
+  error!("X11 error: {:#?}", error);
+
+  // Fun fact: this return value is completely ignored.
+  0
+}
+
 /// Error returned if this system doesn't have XLib or can't create an X connection.
 #[derive(Clone, Debug)]
 pub enum XNotSupported {
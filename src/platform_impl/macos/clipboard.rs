@@ -4,10 +4,17 @@
 use cocoa::{
   appkit::NSPasteboardTypeString,
   base::{id, nil, BOOL, YES},
-  foundation::{NSInteger, NSString},
+  foundation::{NSArray, NSInteger, NSString, NSUInteger},
 };
 use objc::{class, msg_send, sel, sel_impl};
 
+use std::{io::Cursor, path::PathBuf};
+
+use crate::clipboard::{ClipboardFiles, ClipboardFormat, ClipboardImage, FileOperation, FormatId};
+
+const HTML_FORMAT: FormatId = "public.html";
+const PNG_FORMAT: FormatId = "public.png";
+
 #[derive(Debug, Clone, Default)]
 pub struct Clipboard;
 
@@ -39,4 +46,180 @@ impl Clipboard {
       }
     }
   }
+
+  pub(crate) fn request_text<F: FnOnce(Option<String>) + Send + 'static>(&self, callback: F) {
+    callback(self.read_text())
+  }
+
+  pub(crate) fn put_formats(&mut self, formats: &[ClipboardFormat]) {
+    unsafe {
+      let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+      let _: NSInteger = msg_send![pasteboard, clearContents];
+      for format in formats {
+        let format_type = NSString::alloc(nil).init_str(format.identifier);
+        let length = format.data.len() as NSUInteger;
+        let nsdata: id =
+          msg_send![class!(NSData), dataWithBytes: format.data.as_ptr() length: length];
+        let result: BOOL = msg_send![pasteboard, setData: nsdata forType: format_type];
+        if result != YES {
+          #[cfg(debug_assertions)]
+          println!("failed to set clipboard for fmt {}", &format.identifier);
+        }
+      }
+    }
+  }
+
+  pub(crate) fn read_format(&self, format: &str) -> Option<Vec<u8>> {
+    unsafe {
+      let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+      let format_type = NSString::alloc(nil).init_str(format);
+      let contents: id = msg_send![pasteboard, dataForType: format_type];
+      if contents.is_null() {
+        None
+      } else {
+        let bytes: *const u8 = msg_send![contents, bytes];
+        let length: NSUInteger = msg_send![contents, length];
+        Some(std::slice::from_raw_parts(bytes, length as usize).to_vec())
+      }
+    }
+  }
+
+  pub(crate) fn request_format<F: FnOnce(Option<Vec<u8>>) + Send + 'static>(
+    &self,
+    format: &str,
+    callback: F,
+  ) {
+    callback(self.read_format(format))
+  }
+
+  pub(crate) fn store(&mut self) {}
+
+  pub(crate) fn available_formats(&self) -> Vec<String> {
+    unsafe {
+      let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+      let types: id = msg_send![pasteboard, types];
+      if types.is_null() {
+        return Vec::new();
+      }
+      let count: NSUInteger = msg_send![types, count];
+      (0..count)
+        .map(|i| {
+          let format_type: id = msg_send![types, objectAtIndex: i];
+          let slice =
+            std::slice::from_raw_parts(format_type.UTF8String() as *const u8, format_type.len());
+          String::from_utf8_lossy(slice).into_owned()
+        })
+        .collect()
+    }
+  }
+
+  pub(crate) fn write_html(&mut self, html: &str, alt_text: &str) {
+    self.put_formats(&[ClipboardFormat::new(HTML_FORMAT, html), alt_text.into()]);
+  }
+
+  pub(crate) fn read_html(&self) -> Option<String> {
+    String::from_utf8(self.read_format(HTML_FORMAT)?).ok()
+  }
+
+  pub(crate) fn write_image(&mut self, image: &ClipboardImage) {
+    let mut png = Vec::new();
+
+    {
+      let mut encoder = png::Encoder::new(Cursor::new(&mut png), image.width, image.height);
+      encoder.set_color(png::ColorType::Rgba);
+      encoder.set_depth(png::BitDepth::Eight);
+
+      let mut writer = match encoder.write_header() {
+        Ok(writer) => writer,
+        Err(_) => return,
+      };
+      if writer.write_image_data(&image.rgba).is_err() {
+        return;
+      }
+    }
+
+    self.put_formats(&[ClipboardFormat::new(PNG_FORMAT, png)]);
+  }
+
+  pub(crate) fn read_image(&self) -> Option<ClipboardImage> {
+    let data = self.read_format(PNG_FORMAT)?;
+
+    let mut decoder = png::Decoder::new(Cursor::new(data));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().ok()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).ok()?;
+    buf.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+      png::ColorType::Rgba => buf,
+      png::ColorType::Rgb => buf
+        .chunks_exact(3)
+        .flat_map(|p| [p[0], p[1], p[2], u8::MAX])
+        .collect(),
+      png::ColorType::GrayscaleAlpha => buf
+        .chunks_exact(2)
+        .flat_map(|p| [p[0], p[0], p[0], p[1]])
+        .collect(),
+      png::ColorType::Grayscale => buf.iter().flat_map(|&p| [p, p, p, u8::MAX]).collect(),
+      png::ColorType::Indexed => return None,
+    };
+
+    Some(ClipboardImage {
+      rgba,
+      width: info.width,
+      height: info.height,
+    })
+  }
+
+  pub(crate) fn write_files(&mut self, paths: &[PathBuf], _operation: FileOperation) {
+    unsafe {
+      let urls: Vec<id> = paths
+        .iter()
+        .map(|path| {
+          let path = NSString::alloc(nil).init_str(&path.to_string_lossy());
+          msg_send![class!(NSURL), fileURLWithPath: path]
+        })
+        .collect();
+      let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+      let _: NSInteger = msg_send![pasteboard, clearContents];
+      let result: BOOL = msg_send![pasteboard, writeObjects: NSArray::arrayWithObjects(nil, &urls)];
+      if result != YES {
+        #[cfg(debug_assertions)]
+        println!("failed to set clipboard");
+      }
+    }
+  }
+
+  pub(crate) fn read_files(&self) -> Option<ClipboardFiles> {
+    unsafe {
+      let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+      let classes = NSArray::arrayWithObjects(nil, &[class!(NSURL) as *const _ as id]);
+      let urls: id = msg_send![pasteboard, readObjectsForClasses: classes options: nil];
+      if urls.is_null() {
+        return None;
+      }
+
+      let mut paths = Vec::new();
+      for i in 0..urls.count() {
+        let url = urls.objectAtIndex(i);
+        let is_file_url: BOOL = msg_send![url, isFileURL];
+        if is_file_url != YES {
+          continue;
+        }
+        let path: id = msg_send![url, path];
+        let slice = std::slice::from_raw_parts(path.UTF8String() as *const u8, path.len());
+        paths.push(PathBuf::from(std::str::from_utf8_unchecked(slice)));
+      }
+
+      if paths.is_empty() {
+        None
+      } else {
+        Some(ClipboardFiles {
+          paths,
+          operation: FileOperation::Copy,
+        })
+      }
+    }
+  }
 }
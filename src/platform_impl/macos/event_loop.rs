@@ -24,7 +24,9 @@ use scopeguard::defer;
 
 use crate::{
   event::Event,
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget},
+  event_loop::{
+    ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget,
+  },
   monitor::MonitorHandle as RootMonitorHandle,
   platform_impl::platform::{
     app::APP_CLASS,
@@ -78,6 +80,9 @@ impl<T> Default for EventLoopWindowTarget<T> {
 }
 
 impl<T: 'static> EventLoopWindowTarget<T> {
+  #[inline]
+  pub fn set_device_event_filter(&self, _filter: DeviceEventFilter) {}
+
   #[inline]
   pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
     monitor::available_monitors()
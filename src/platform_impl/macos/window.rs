@@ -34,7 +34,8 @@ use crate::{
     OsError,
   },
   window::{
-    CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+    CursorIcon, DragData, DragOptions, Fullscreen, Theme, UserAttentionType, WindowAttributes,
+    WindowId as RootWindowId,
   },
 };
 use cocoa::{
@@ -293,18 +294,24 @@ pub(super) fn get_ns_theme() -> Theme {
   }
 }
 
-pub(super) fn set_ns_theme(theme: Theme) {
-  let name = match theme {
-    Theme::Dark => "NSAppearanceNameDarkAqua",
-    Theme::Light => "NSAppearanceNameAqua",
-  };
+/// Sets the appearance of the application, or makes it follow the system if `None`.
+pub(super) fn set_ns_theme(theme: Option<Theme>) {
   unsafe {
     let app_class = class!(NSApplication);
     let app: id = msg_send![app_class, sharedApplication];
     let has_theme: BOOL = msg_send![app, respondsToSelector: sel!(effectiveAppearance)];
     if has_theme == YES {
-      let name = NSString::alloc(nil).init_str(name);
-      let appearance: id = msg_send![class!(NSAppearance), appearanceNamed: name];
+      let appearance: id = match theme {
+        Some(theme) => {
+          let name = match theme {
+            Theme::Dark => "NSAppearanceNameDarkAqua",
+            Theme::Light => "NSAppearanceNameAqua",
+          };
+          let name = NSString::alloc(nil).init_str(name);
+          msg_send![class!(NSAppearance), appearanceNamed: name]
+        }
+        None => nil,
+      };
       let _: () = msg_send![app, setAppearance: appearance];
     }
   }
@@ -500,7 +507,7 @@ impl UnownedWindow {
 
     match cloned_preferred_theme {
       Some(theme) => {
-        set_ns_theme(theme);
+        set_ns_theme(Some(theme));
         let mut state = window.shared_state.lock().unwrap();
         state.current_theme = theme.clone();
       }
@@ -746,6 +753,11 @@ impl UnownedWindow {
     Ok(())
   }
 
+  #[inline]
+  pub fn start_drag(&self, _data: DragData, _options: DragOptions) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
   #[inline]
   pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<(), ExternalError> {
     unsafe {
@@ -1218,6 +1230,33 @@ impl UnownedWindow {
     RawDisplayHandle::AppKit(AppKitDisplayHandle::empty())
   }
 
+  #[inline]
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    set_ns_theme(theme);
+    // The appearance is shared by all the windows, let their delegates update their current
+    // theme and emit `ThemeChanged`.
+    unsafe {
+      let app: id = msg_send![class!(NSApplication), sharedApplication];
+      let windows: id = msg_send![app, windows];
+      for i in 0..windows.count() {
+        let window = windows.objectAtIndex(i);
+        let delegate: id = msg_send![window, delegate];
+        let responds: BOOL = msg_send![
+          delegate,
+          respondsToSelector: sel!(effectiveAppearanceDidChangedOnMainThread:)
+        ];
+        if responds == YES {
+          let _: () = msg_send![
+            delegate,
+            performSelectorOnMainThread: sel!(effectiveAppearanceDidChangedOnMainThread:)
+            withObject: nil
+            waitUntilDone: false
+          ];
+        }
+      }
+    }
+  }
+
   #[inline]
   pub fn theme(&self) -> Theme {
     let state = self.shared_state.lock().unwrap();
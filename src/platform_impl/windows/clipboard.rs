@@ -1,18 +1,23 @@
 // Copyright 2019-2021 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::clipboard::{ClipboardFormat, FormatId};
-use std::{ffi::OsStr, os::windows::ffi::OsStrExt, ptr};
+use crate::clipboard::{ClipboardFiles, ClipboardFormat, ClipboardImage, FileOperation, FormatId};
+use std::{
+  ffi::{OsStr, OsString},
+  os::windows::ffi::{OsStrExt, OsStringExt},
+  path::PathBuf,
+  ptr,
+};
 use windows::{
   core::PWSTR,
   Win32::{
     Foundation::{HANDLE, HWND},
     System::{
       DataExchange::{
-        CloseClipboard, EmptyClipboard, GetClipboardData, OpenClipboard, RegisterClipboardFormatA,
-        SetClipboardData,
+        CloseClipboard, EmptyClipboard, EnumClipboardFormats, GetClipboardData,
+        GetClipboardFormatNameW, OpenClipboard, RegisterClipboardFormatA, SetClipboardData,
       },
-      Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+      Memory::{GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE},
       SystemServices::CF_UNICODETEXT,
     },
   },
@@ -52,10 +57,15 @@ impl Clipboard {
     .flatten()
   }
 
+  pub(crate) fn request_text<F: FnOnce(Option<String>) + Send + 'static>(&self, callback: F) {
+    callback(self.read_text())
+  }
+
   pub(crate) fn put_formats(&mut self, formats: &[ClipboardFormat]) {
     with_clipboard(|| unsafe {
       EmptyClipboard();
 
+      let mut sizes = Vec::new();
       for format in formats {
         let handle = make_handle(format);
         let format_id = match get_format_id(format.identifier) {
@@ -66,19 +76,317 @@ impl Clipboard {
             continue;
           }
         };
-        if let Err(err) = SetClipboardData(format_id, handle) {
-          #[cfg(debug_assertions)]
-          println!(
-            "failed to set clipboard for fmt {}, error: {}",
-            &format.identifier, err
-          );
+        match SetClipboardData(format_id, handle) {
+          Ok(_) => {
+            if format.identifier != ClipboardFormat::TEXT {
+              sizes.push((format_id, format.data.len()));
+            }
+          }
+          Err(err) => {
+            #[cfg(debug_assertions)]
+            println!(
+              "failed to set clipboard for fmt {}, error: {}",
+              &format.identifier, err
+            );
+          }
         }
       }
+
+      // The global memory blocks may be larger than the data, so the exact sizes are written
+      // alongside it to be read back by `read_format`.
+      if let Some(format_id) = register_identifier(FORMAT_SIZES) {
+        let _ = SetClipboardData(format_id, make_data_handle(&encode_format_sizes(&sizes)));
+      }
     });
   }
+
+  pub(crate) fn read_format(&self, format: &str) -> Option<Vec<u8>> {
+    if format == ClipboardFormat::TEXT {
+      return self.read_text().map(String::into_bytes);
+    }
+
+    let format_id = get_format_id(format)?;
+    with_clipboard(|| unsafe {
+      let mut data = read_handle(format_id)?;
+      // Only the formats written by tao have a known size, the others keep the padding of their
+      // memory block.
+      let size = register_identifier(FORMAT_SIZES)
+        .and_then(|sizes_id| read_handle(sizes_id))
+        .and_then(|sizes| format_size(&sizes, format_id));
+      if let Some(size) = size {
+        data.truncate(size);
+      }
+      Some(data)
+    })
+    .flatten()
+  }
+
+  pub(crate) fn request_format<F: FnOnce(Option<Vec<u8>>) + Send + 'static>(
+    &self,
+    format: &str,
+    callback: F,
+  ) {
+    callback(self.read_format(format))
+  }
+
+  pub(crate) fn store(&mut self) {}
+
+  pub(crate) fn available_formats(&self) -> Vec<String> {
+    with_clipboard(|| unsafe {
+      let mut formats = Vec::new();
+      let mut format_id = EnumClipboardFormats(0);
+      while format_id != 0 {
+        if let Some(name) = get_format_name(format_id) {
+          if name != FORMAT_SIZES {
+            formats.push(name);
+          }
+        }
+        format_id = EnumClipboardFormats(format_id);
+      }
+      formats
+    })
+    .unwrap_or_default()
+  }
+
+  pub(crate) fn write_html(&mut self, html: &str, alt_text: &str) {
+    self.put_formats(&[
+      ClipboardFormat::new(HTML_FORMAT, html_to_cf_html(html)),
+      alt_text.into(),
+    ]);
+  }
+
+  pub(crate) fn read_html(&self) -> Option<String> {
+    cf_html_to_html(&self.read_format(HTML_FORMAT)?)
+  }
+
+  pub(crate) fn write_image(&mut self, image: &ClipboardImage) {
+    self.put_formats(&[ClipboardFormat::new("CF_DIBV5", image_to_dibv5(image))]);
+  }
+
+  pub(crate) fn read_image(&self) -> Option<ClipboardImage> {
+    // Windows synthesizes `CF_DIB` from `CF_DIBV5` and `CF_BITMAP` when needed.
+    self
+      .read_format("CF_DIB")
+      .and_then(|dib| dib_to_image(&dib))
+  }
+
+  pub(crate) fn write_files(&mut self, paths: &[PathBuf], operation: FileOperation) {
+    let effect = match operation {
+      FileOperation::Copy => DROPEFFECT_COPY,
+      FileOperation::Cut => DROPEFFECT_MOVE,
+    };
+    self.put_formats(&[
+      ClipboardFormat::new("CF_HDROP", paths_to_hdrop(paths)),
+      ClipboardFormat::new(PREFERRED_DROP_EFFECT, effect.to_le_bytes()),
+    ]);
+  }
+
+  pub(crate) fn read_files(&self) -> Option<ClipboardFiles> {
+    let paths = hdrop_to_paths(&self.read_format("CF_HDROP")?)?;
+    let effect = self
+      .read_format(PREFERRED_DROP_EFFECT)
+      .and_then(|effect| Some(u32::from_le_bytes(effect.get(..4)?.try_into().ok()?)))
+      .unwrap_or(DROPEFFECT_COPY);
+    let operation = if effect & DROPEFFECT_MOVE != 0 {
+      FileOperation::Cut
+    } else {
+      FileOperation::Copy
+    };
+    Some(ClipboardFiles { paths, operation })
+  }
+}
+
+const HTML_FORMAT: FormatId = "HTML Format";
+
+/// Wraps an HTML fragment in the `CF_HTML` format, a header with byte offsets followed by an
+/// HTML document.
+///
+/// See <https://docs.microsoft.com/en-us/windows/win32/dataxchg/html-clipboard-format>
+fn html_to_cf_html(html: &str) -> String {
+  const PREFIX: &str = "<html><body>\r\n<!--StartFragment-->";
+  const SUFFIX: &str = "<!--EndFragment-->\r\n</body></html>";
+  let header = |start_html: usize, end_html: usize, start_fragment: usize, end_fragment: usize| {
+    format!(
+      "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+      start_html, end_html, start_fragment, end_fragment
+    )
+  };
+
+  // The offsets have a fixed width, so the header length doesn't depend on them.
+  let start_html = header(0, 0, 0, 0).len();
+  let start_fragment = start_html + PREFIX.len();
+  let end_fragment = start_fragment + html.len();
+  let end_html = end_fragment + SUFFIX.len();
+  format!(
+    "{}{}{}{}",
+    header(start_html, end_html, start_fragment, end_fragment),
+    PREFIX,
+    html,
+    SUFFIX
+  )
 }
 
-fn get_format_id(format: FormatId) -> Option<u32> {
+/// Extracts the HTML fragment of `CF_HTML` data.
+fn cf_html_to_html(data: &[u8]) -> Option<String> {
+  let data = data.split(|b| *b == 0).next()?;
+  let header = String::from_utf8_lossy(data);
+  let offset = |key: &str| -> Option<usize> {
+    let start = header.find(key)? + key.len();
+    header[start..].lines().next()?.trim().parse().ok()
+  };
+  let fragment = data.get(offset("StartFragment:")?..offset("EndFragment:")?)?;
+  String::from_utf8(fragment.to_vec()).ok()
+}
+
+const PREFERRED_DROP_EFFECT: FormatId = "Preferred DropEffect";
+const DROPEFFECT_COPY: u32 = 1;
+const DROPEFFECT_MOVE: u32 = 2;
+const DROPFILES_SIZE: u32 = 20;
+
+/// Encodes the paths as a `DROPFILES` structure followed by a double-null-terminated list of
+/// wide strings.
+fn paths_to_hdrop(paths: &[PathBuf]) -> Vec<u8> {
+  let mut hdrop = Vec::new();
+  hdrop.extend_from_slice(&DROPFILES_SIZE.to_le_bytes()); // pFiles
+  hdrop.extend_from_slice(&0i32.to_le_bytes()); // pt.x
+  hdrop.extend_from_slice(&0i32.to_le_bytes()); // pt.y
+  hdrop.extend_from_slice(&0u32.to_le_bytes()); // fNC
+  hdrop.extend_from_slice(&1u32.to_le_bytes()); // fWide
+  for path in paths {
+    for c in path.as_os_str().encode_wide().chain(Some(0)) {
+      hdrop.extend_from_slice(&c.to_le_bytes());
+    }
+  }
+  hdrop.extend_from_slice(&0u16.to_le_bytes());
+  hdrop
+}
+
+/// Decodes the list of paths of a `DROPFILES` structure, as found in `CF_HDROP`.
+fn hdrop_to_paths(hdrop: &[u8]) -> Option<Vec<PathBuf>> {
+  let u32_at = |offset: usize| -> Option<u32> {
+    Some(u32::from_le_bytes(
+      hdrop.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+  };
+  let files = hdrop.get(u32_at(0)? as usize..)?;
+  let wide = u32_at(16)? != 0;
+
+  let paths: Vec<PathBuf> = if wide {
+    let files: Vec<u16> = files
+      .chunks_exact(2)
+      .map(|c| u16::from_le_bytes([c[0], c[1]]))
+      .collect();
+    files
+      .split(|c| *c == 0)
+      .take_while(|path| !path.is_empty())
+      .map(|path| OsString::from_wide(path).into())
+      .collect()
+  } else {
+    files
+      .split(|c| *c == 0)
+      .take_while(|path| !path.is_empty())
+      .map(|path| String::from_utf8_lossy(path).into_owned().into())
+      .collect()
+  };
+
+  if paths.is_empty() {
+    None
+  } else {
+    Some(paths)
+  }
+}
+
+const BITMAPV5HEADER_SIZE: u32 = 124;
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const LCS_SRGB: u32 = 0x7352_4742;
+
+/// Encodes the image as a top-down, 32bpp `BITMAPV5HEADER` DIB with an alpha channel.
+fn image_to_dibv5(image: &ClipboardImage) -> Vec<u8> {
+  let mut dib = Vec::with_capacity(BITMAPV5HEADER_SIZE as usize + image.rgba.len());
+  dib.extend_from_slice(&BITMAPV5HEADER_SIZE.to_le_bytes()); // bV5Size
+  dib.extend_from_slice(&(image.width as i32).to_le_bytes()); // bV5Width
+  dib.extend_from_slice(&(-(image.height as i32)).to_le_bytes()); // bV5Height, negative for top-down
+  dib.extend_from_slice(&1u16.to_le_bytes()); // bV5Planes
+  dib.extend_from_slice(&32u16.to_le_bytes()); // bV5BitCount
+  dib.extend_from_slice(&BI_BITFIELDS.to_le_bytes()); // bV5Compression
+  dib.extend_from_slice(&(image.rgba.len() as u32).to_le_bytes()); // bV5SizeImage
+  dib.extend_from_slice(&0i32.to_le_bytes()); // bV5XPelsPerMeter
+  dib.extend_from_slice(&0i32.to_le_bytes()); // bV5YPelsPerMeter
+  dib.extend_from_slice(&0u32.to_le_bytes()); // bV5ClrUsed
+  dib.extend_from_slice(&0u32.to_le_bytes()); // bV5ClrImportant
+  dib.extend_from_slice(&0x00FF_0000u32.to_le_bytes()); // bV5RedMask
+  dib.extend_from_slice(&0x0000_FF00u32.to_le_bytes()); // bV5GreenMask
+  dib.extend_from_slice(&0x0000_00FFu32.to_le_bytes()); // bV5BlueMask
+  dib.extend_from_slice(&0xFF00_0000u32.to_le_bytes()); // bV5AlphaMask
+  dib.extend_from_slice(&LCS_SRGB.to_le_bytes()); // bV5CSType
+  dib.resize(BITMAPV5HEADER_SIZE as usize, 0); // endpoints, gamma, intent and profile
+  for pixel in image.rgba.chunks_exact(4) {
+    dib.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+  }
+  dib
+}
+
+/// Decodes an uncompressed 24bpp or 32bpp DIB, as found in `CF_DIB`.
+fn dib_to_image(dib: &[u8]) -> Option<ClipboardImage> {
+  let u32_at = |offset: usize| -> Option<u32> {
+    Some(u32::from_le_bytes(
+      dib.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+  };
+  let u16_at = |offset: usize| -> Option<u16> {
+    Some(u16::from_le_bytes(
+      dib.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+  };
+
+  let header_size = u32_at(0)? as usize;
+  let width = u32_at(4)? as i32;
+  let height = u32_at(8)? as i32;
+  let bit_count = u16_at(14)?;
+  let compression = u32_at(16)?;
+  let colors_used = u32_at(32)? as usize;
+  if width <= 0 || height == 0 || !(bit_count == 24 || bit_count == 32) {
+    return None;
+  }
+  let masks_size = match compression {
+    BI_RGB => 0,
+    // The color masks follow a plain `BITMAPINFOHEADER`, they are part of the larger headers.
+    BI_BITFIELDS if header_size == 40 => 12,
+    BI_BITFIELDS => 0,
+    _ => return None,
+  };
+
+  let (width, top_down, height) = (width as usize, height < 0, height.unsigned_abs() as usize);
+  let bytes_per_pixel = bit_count as usize / 8;
+  let stride = (width * bit_count as usize + 31) / 32 * 4;
+  let pixels = dib.get(header_size + masks_size + colors_used * 4..)?;
+  if pixels.len() < stride * height {
+    return None;
+  }
+
+  // 32bpp `BI_RGB` bitmaps usually leave the alpha channel empty.
+  let has_alpha = bit_count == 32
+    && (compression == BI_BITFIELDS || pixels.chunks_exact(4).any(|pixel| pixel[3] != 0));
+
+  let mut rgba = Vec::with_capacity(width * height * 4);
+  for row in 0..height {
+    let row = if top_down { row } else { height - row - 1 };
+    let row = &pixels[row * stride..row * stride + width * bytes_per_pixel];
+    for pixel in row.chunks_exact(bytes_per_pixel) {
+      let alpha = if has_alpha { pixel[3] } else { u8::MAX };
+      rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], alpha]);
+    }
+  }
+
+  Some(ClipboardImage {
+    rgba,
+    width: width as u32,
+    height: height as u32,
+  })
+}
+
+fn get_format_id(format: &str) -> Option<u32> {
   if let Some((id, _)) = STANDARD_FORMATS.iter().find(|(_, s)| s == &format) {
     return Some(*id);
   }
@@ -88,6 +396,20 @@ fn get_format_id(format: FormatId) -> Option<u32> {
   }
 }
 
+fn get_format_name(format_id: u32) -> Option<String> {
+  if let Some((_, name)) = STANDARD_FORMATS.iter().find(|(id, _)| *id == format_id) {
+    return Some(name.to_string());
+  }
+  unsafe {
+    let mut name = [0u16; 256];
+    let len = GetClipboardFormatNameW(format_id, PWSTR(name.as_mut_ptr()), name.len() as i32);
+    if len <= 0 {
+      return None;
+    }
+    String::from_utf16(&name[..len as usize]).ok()
+  }
+}
+
 fn register_identifier(ident: &str) -> Option<u32> {
   unsafe {
     let pb_format = RegisterClipboardFormatA(ident);
@@ -105,20 +427,67 @@ fn register_identifier(ident: &str) -> Option<u32> {
 }
 
 unsafe fn make_handle(format: &ClipboardFormat) -> HANDLE {
-  HANDLE(if format.identifier == ClipboardFormat::TEXT {
-    let s: &OsStr = std::str::from_utf8_unchecked(&format.data).as_ref();
-    let wstr: Vec<u16> = s.encode_wide().chain(Some(0)).collect();
+  if format.identifier == ClipboardFormat::TEXT {
+    // The data of a `ClipboardFormat` can be any bytes, so it isn't trusted to be UTF-8.
+    let s = String::from_utf8_lossy(&format.data);
+    let wstr: Vec<u16> = OsStr::new(&*s).encode_wide().chain(Some(0)).collect();
     let handle = GlobalAlloc(GMEM_MOVEABLE, wstr.len() * std::mem::size_of::<u16>());
     let locked = GlobalLock(handle) as *mut _;
     ptr::copy_nonoverlapping(wstr.as_ptr(), locked, wstr.len());
     GlobalUnlock(handle);
-    handle
+    HANDLE(handle)
   } else {
-    let handle = GlobalAlloc(GMEM_MOVEABLE, format.data.len() * std::mem::size_of::<u8>());
-    let locked = GlobalLock(handle) as *mut _;
-    ptr::copy_nonoverlapping(format.data.as_ptr(), locked, format.data.len());
-    GlobalUnlock(handle);
-    handle
+    make_data_handle(&format.data)
+  }
+}
+
+unsafe fn make_data_handle(data: &[u8]) -> HANDLE {
+  let handle = GlobalAlloc(GMEM_MOVEABLE, data.len() * std::mem::size_of::<u8>());
+  let locked = GlobalLock(handle) as *mut _;
+  ptr::copy_nonoverlapping(data.as_ptr(), locked, data.len());
+  GlobalUnlock(handle);
+  HANDLE(handle)
+}
+
+/// The content of the clipboard for `format_id`, including the padding of its memory block.
+///
+/// The clipboard must be open.
+unsafe fn read_handle(format_id: u32) -> Option<Vec<u8>> {
+  let handle = GetClipboardData(format_id).unwrap_or_default();
+  if handle.is_invalid() {
+    return None;
+  }
+  let size = GlobalSize(handle.0);
+  let locked = GlobalLock(handle.0) as *const u8;
+  if locked.is_null() {
+    return None;
+  }
+  let data = std::slice::from_raw_parts(locked, size).to_vec();
+  GlobalUnlock(handle.0);
+  Some(data)
+}
+
+/// The private format holding the sizes of the formats written by tao.
+const FORMAT_SIZES: FormatId = "Tao Format Sizes";
+
+/// Encodes the format ids and sizes as pairs of a little-endian `u32` and `u64`.
+fn encode_format_sizes(sizes: &[(u32, usize)]) -> Vec<u8> {
+  let mut data = Vec::new();
+  for (format_id, size) in sizes {
+    data.extend_from_slice(&format_id.to_le_bytes());
+    data.extend_from_slice(&(*size as u64).to_le_bytes());
+  }
+  data
+}
+
+/// The size of `format_id` in data encoded with `encode_format_sizes`.
+fn format_size(sizes: &[u8], format_id: u32) -> Option<usize> {
+  sizes.chunks_exact(12).find_map(|entry| {
+    if u32::from_le_bytes(entry[..4].try_into().ok()?) == format_id {
+      Some(u64::from_le_bytes(entry[4..].try_into().ok()?) as usize)
+    } else {
+      None
+    }
   })
 }
 
@@ -165,3 +534,38 @@ static STANDARD_FORMATS: &[(u32, &str)] = &[
   (0x0300, "CF_GDIOBJFIRST"),
   (0x03FF, "CF_GDIOBJLAST"),
 ];
+
+#[test]
+fn dib_round_trip() {
+  let image = ClipboardImage {
+    rgba: vec![255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0, 10, 20, 30, 40],
+    width: 2,
+    height: 2,
+  };
+  assert_eq!(dib_to_image(&image_to_dibv5(&image)), Some(image));
+}
+
+#[test]
+fn hdrop_round_trip() {
+  let paths = vec![PathBuf::from("C:\\a b.txt"), PathBuf::from("D:\\ü\\c")];
+  assert_eq!(hdrop_to_paths(&paths_to_hdrop(&paths)), Some(paths));
+}
+
+#[test]
+fn cf_html_round_trip() {
+  let html = "<b>tao</b> – ünïcödé";
+  assert_eq!(
+    cf_html_to_html(html_to_cf_html(html).as_bytes()),
+    Some(html.to_string())
+  );
+}
+
+#[test]
+fn format_sizes_round_trip() {
+  let sizes = encode_format_sizes(&[(49161, 3), (15, 1024)]);
+  // The memory block of the sizes may be padded as well.
+  let padded = [sizes.as_slice(), &[0; 7]].concat();
+  assert_eq!(format_size(&padded, 49161), Some(3));
+  assert_eq!(format_size(&padded, 15), Some(1024));
+  assert_eq!(format_size(&padded, 13), None);
+}
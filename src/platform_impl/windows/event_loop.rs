@@ -27,6 +27,7 @@ use windows::{
     },
     Graphics::Gdi::*,
     System::{
+      DataExchange::AddClipboardFormatListener,
       LibraryLoader::GetModuleHandleW,
       Ole::{IDropTarget, RevokeDragDrop},
       Threading::GetCurrentThreadId,
@@ -45,7 +46,7 @@ use crate::{
   accelerator::AcceleratorId,
   dpi::{PhysicalPosition, PhysicalSize},
   event::{DeviceEvent, Event, Force, RawKeyEvent, Touch, TouchPhase, WindowEvent},
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
+  event_loop::{ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW},
   keyboard::{KeyCode, ModifiersState},
   monitor::MonitorHandle as RootMonitorHandle,
   platform_impl::platform::{
@@ -188,7 +189,11 @@ impl<T: 'static> EventLoop<T> {
     let runner_shared = Rc::new(EventLoopRunner::new(thread_msg_target, wait_thread_id));
 
     let thread_msg_sender = subclass_event_target_window(thread_msg_target, runner_shared.clone());
-    raw_input::register_all_mice_and_keyboards_for_raw_input(thread_msg_target);
+    raw_input::register_all_mice_and_keyboards_for_raw_input(
+      thread_msg_target,
+      DeviceEventFilter::default(),
+    );
+    unsafe { AddClipboardFormatListener(thread_msg_target) };
 
     EventLoop {
       thread_msg_sender,
@@ -289,6 +294,11 @@ impl<T: 'static> EventLoop<T> {
 }
 
 impl<T> EventLoopWindowTarget<T> {
+  #[inline]
+  pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+    raw_input::register_all_mice_and_keyboards_for_raw_input(self.thread_msg_target, filter);
+  }
+
   #[inline(always)]
   pub(crate) fn create_thread_executor(&self) -> EventLoopThreadExecutor {
     EventLoopThreadExecutor {
@@ -2004,20 +2014,18 @@ unsafe fn public_window_callback_inner<T: 'static>(
     win32wm::WM_WININICHANGE => {
       use crate::event::WindowEvent::ThemeChanged;
 
+      // Also sent by `Window::set_theme`, so the preferred theme is applied even when set.
       let preferred_theme = subclass_input.window_state.lock().preferred_theme;
+      let new_theme = try_theme(window, preferred_theme);
+      let mut window_state = subclass_input.window_state.lock();
 
-      if preferred_theme == None {
-        let new_theme = try_theme(window, preferred_theme);
-        let mut window_state = subclass_input.window_state.lock();
-
-        if window_state.current_theme != new_theme {
-          window_state.current_theme = new_theme;
-          mem::drop(window_state);
-          subclass_input.send_event(Event::WindowEvent {
-            window_id: RootWindowId(WindowId(window.0)),
-            event: ThemeChanged(new_theme),
-          });
-        }
+      if window_state.current_theme != new_theme {
+        window_state.current_theme = new_theme;
+        mem::drop(window_state);
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: ThemeChanged(new_theme),
+        });
       }
     }
 
@@ -2158,6 +2166,11 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
       LRESULT(0)
     }
 
+    win32wm::WM_CLIPBOARDUPDATE => {
+      subclass_input.send_event(Event::ClipboardChanged);
+      LRESULT(0)
+    }
+
     win32wm::WM_INPUT => {
       if let Some(data) = raw_input::get_raw_input_data(HRAWINPUT(lparam.0)) {
         handle_raw_input(&subclass_input, data);
@@ -15,7 +15,7 @@ use windows::Win32::{
   },
 };
 
-use crate::{event::ElementState, platform_impl::platform::util};
+use crate::{event::ElementState, event_loop::DeviceEventFilter, platform_impl::platform::util};
 
 #[allow(dead_code)]
 pub fn get_raw_input_device_list() -> Option<Vec<RAWINPUTDEVICELIST>> {
@@ -130,10 +130,21 @@ pub fn register_raw_input_devices(devices: &[RAWINPUTDEVICE]) -> bool {
   success.as_bool()
 }
 
-pub fn register_all_mice_and_keyboards_for_raw_input(window_handle: HWND) -> bool {
+pub fn register_all_mice_and_keyboards_for_raw_input(
+  mut window_handle: HWND,
+  filter: DeviceEventFilter,
+) -> bool {
   // RIDEV_DEVNOTIFY: receive hotplug events
   // RIDEV_INPUTSINK: receive events even if we're not in the foreground
-  let flags = RAWINPUTDEVICE_FLAGS(RIDEV_DEVNOTIFY.0 | RIDEV_INPUTSINK.0);
+  // RIDEV_REMOVE: don't receive device events (requires a null hwndTarget)
+  let flags = match filter {
+    DeviceEventFilter::Always => {
+      window_handle = HWND::default();
+      RIDEV_REMOVE
+    }
+    DeviceEventFilter::Unfocused => RIDEV_DEVNOTIFY,
+    DeviceEventFilter::Never => RAWINPUTDEVICE_FLAGS(RIDEV_DEVNOTIFY.0 | RIDEV_INPUTSINK.0),
+  };
 
   let devices: [RAWINPUTDEVICE; 2] = [
     RAWINPUTDEVICE {
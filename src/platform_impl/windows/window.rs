@@ -52,8 +52,8 @@ use crate::{
     OsError, Parent, PlatformSpecificWindowBuilderAttributes, WindowId,
   },
   window::{
-    CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
-    BORDERLESS_RESIZE_INSET,
+    CursorIcon, DragData, DragOptions, Fullscreen, Theme, UserAttentionType, WindowAttributes,
+    WindowId as RootWindowId, BORDERLESS_RESIZE_INSET,
   },
 };
 
@@ -412,6 +412,11 @@ impl Window {
     Ok(())
   }
 
+  #[inline]
+  pub fn start_drag(&self, _data: DragData, _options: DragOptions) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
   #[inline]
   pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<(), ExternalError> {
     let window = self.window.clone();
@@ -734,6 +739,15 @@ impl Window {
     });
   }
 
+  #[inline]
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    self.window_state.lock().preferred_theme = theme;
+    // The theme is applied and `ThemeChanged` emitted by the window procedure.
+    unsafe {
+      SendMessageW(self.hwnd(), WM_WININICHANGE, WPARAM(0), LPARAM(0));
+    }
+  }
+
   #[inline]
   pub fn theme(&self) -> Theme {
     self.window_state.lock().current_theme
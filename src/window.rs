@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! The `Window` struct and associated types.
-use std::fmt;
+use std::{fmt, path::PathBuf};
 
 use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle};
 
@@ -10,6 +10,7 @@ use crate::{
   dpi::{PhysicalPosition, PhysicalSize, Position, Size},
   error::{ExternalError, NotSupportedError, OsError},
   event_loop::EventLoopWindowTarget,
+  icon::RgbaIcon,
   menu::MenuBar,
   monitor::{MonitorHandle, VideoMode},
   platform_impl::{self, NativeHandle},
@@ -846,6 +847,19 @@ impl Window {
     self.window.is_menu_visible()
   }
 
+  /// Sets the theme of the window, or makes it follow the theme of the system if `None`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The theme applies to all the windows of the application, since the GTK
+  ///   settings are global.
+  /// - **macOS:** The theme applies to all the windows of the application.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    self.window.set_theme(theme)
+  }
+
   /// Returns the current window theme.
   ///
   /// ## Platform-specific
@@ -887,6 +901,9 @@ impl Window {
   /// ## Platform-specific
   ///
   /// - **macOS:** This locks the cursor in a fixed location, which looks visually awkward.
+  /// - **Linux:** The grab is released while the window is unfocused and taken again when it
+  ///   regains focus.
+  /// - **Linux (Wayland):** Always returns an [`ExternalError::NotSupported`].
   /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
   #[inline]
   pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
@@ -922,6 +939,22 @@ impl Window {
     self.window.drag_window()
   }
 
+  /// Starts dragging `data` out of the window, so it can be dropped in other applications.
+  ///
+  /// There's no guarantee that this will work unless a mouse button was pressed immediately
+  /// before this function is called. Once the data is dropped or the drag is cancelled,
+  /// [`WindowEvent::DragEnded`] is emitted with the resulting [`DropAction`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+  ///
+  /// [`WindowEvent::DragEnded`]: crate::event::WindowEvent::DragEnded
+  #[inline]
+  pub fn start_drag(&self, data: DragData, options: DragOptions) -> Result<(), ExternalError> {
+    self.window.start_drag(data, options)
+  }
+
   /// Modifies whether the window catches cursor events.
   ///
   /// If `true`, the events are passed through the window such that any other window behind it receives them.
@@ -1125,6 +1158,61 @@ impl Default for UserAttentionType {
   }
 }
 
+/// The data carried by a drag started with [`Window::start_drag`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DragData {
+  /// A list of files, offered as URIs.
+  Files(Vec<PathBuf>),
+  /// Plain text.
+  Text(String),
+  /// An image from 32bpp RGBA data.
+  Image(DragImage),
+}
+
+/// An image dragged with [`DragData::Image`], from 32bpp RGBA data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DragImage(pub(crate) RgbaIcon);
+
+impl DragImage {
+  /// Creates a `DragImage` from 32bpp RGBA data.
+  ///
+  /// The length of `rgba` must be divisible by 4, `width * height` must equal `rgba.len() / 4`
+  /// and neither the width nor the height can be 0. Otherwise, this will return a `BadIcon`
+  /// error.
+  pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+    RgbaIcon::from_non_empty_rgba(rgba, width, height).map(DragImage)
+  }
+}
+
+/// Options of a drag started with [`Window::start_drag`].
+#[derive(Debug, Clone, Default)]
+pub struct DragOptions {
+  /// The image shown under the cursor while dragging, centered on it.
+  ///
+  /// If `None`, the platform default is used.
+  pub icon: Option<Icon>,
+  /// Whether the drop target may move the data instead of copying it.
+  ///
+  /// When the data is moved, the application is expected to delete it once
+  /// [`WindowEvent::DragEnded`] is received with [`DropAction::Move`].
+  ///
+  /// [`WindowEvent::DragEnded`]: crate::event::WindowEvent::DragEnded
+  pub allow_move: bool,
+}
+
+/// The outcome of a drag started with [`Window::start_drag`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DropAction {
+  /// The data was copied by the drop target.
+  Copy,
+  /// The data was moved by the drop target.
+  Move,
+  /// The drag was cancelled or the data was refused by the drop target.
+  Cancel,
+}
+
 /// A constant used to determine how much inside the window, the resize handler should appear (only used in Linux(gtk) and Windows).
 /// You probably need to scale it by the scale_factor of the window.
 pub const BORDERLESS_RESIZE_INSET: i32 = 5;
@@ -26,3 +26,9 @@ fn ids_send() {
   needs_send::<tao::event::DeviceId>();
   needs_send::<tao::monitor::MonitorHandle>();
 }
+
+#[test]
+fn clipboard_send() {
+  // ensures that `Clipboard` implements `Send` so it can be used from worker threads
+  needs_send::<tao::clipboard::Clipboard>();
+}